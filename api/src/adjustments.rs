@@ -0,0 +1,218 @@
+use crate::models::*;
+
+// Running monthly amount per cost category for one item. Category names match
+// the quote totals: fixed categories plus the item's SKU category.
+fn category_amounts(cost: &ItemCost) -> Vec<(String, f64)> {
+    let c = &cost.cost;
+    vec![
+        ("compute".to_string(), c.compute_monthly),
+        ("licence".to_string(), c.licence_monthly),
+        ("storage".to_string(), c.storage_monthly),
+        ("backup".to_string(), c.backup_monthly),
+        ("network".to_string(), c.network_monthly),
+        (cost.sku_category.clone().unwrap_or_else(|| "other".to_string()), c.service_monthly),
+    ]
+}
+
+fn in_scope(adjustment: &Adjustment, category: &str) -> bool {
+    adjustment.category.as_deref().is_none_or(|c| c == category)
+}
+
+// Apply one adjustment to the in-scope categories of the target items and return
+// the signed amount it changed. Fixed amounts are spread over the in-scope cost
+// in proportion; discounts never take a category below zero.
+fn apply_one(
+    adjustment: &Adjustment,
+    amounts: &mut [Vec<(String, f64)>],
+    deltas: &mut [f64],
+    targets: &[usize],
+) -> f64 {
+    let base: f64 = targets
+        .iter()
+        .flat_map(|&i| amounts[i].iter())
+        .filter(|(category, _)| in_scope(adjustment, category))
+        .map(|(_, amount)| amount)
+        .sum();
+    if base <= 0.0 {
+        return 0.0;
+    }
+
+    let rate = match adjustment.amount_type {
+        AdjustmentType::Percent => adjustment.value / 100.0,
+        AdjustmentType::Fixed => adjustment.value / base,
+    } * adjustment.kind.sign();
+
+    let mut applied = 0.0;
+    for &i in targets {
+        for (category, amount) in amounts[i].iter_mut() {
+            if !in_scope(adjustment, category) {
+                continue;
+            }
+            let delta = (*amount * rate).max(-*amount);
+            *amount += delta;
+            deltas[i] += delta;
+            applied += delta;
+        }
+    }
+    applied
+}
+
+// Apply adjustments to priced items and fill in their adjustment and net amounts.
+// Order: item-level before quote-level, percentages before fixed amounts, then
+// creation order. Each adjustment applies to the amount left by the previous ones.
+pub fn apply_adjustments(costs: &mut [ItemCost], adjustments: &[Adjustment]) -> Vec<AppliedAdjustment> {
+    let mut ordered: Vec<&Adjustment> = adjustments.iter().collect();
+    ordered.sort_by_key(|a| (a.item_id.is_none(), a.amount_type == AdjustmentType::Fixed));
+
+    let mut amounts: Vec<Vec<(String, f64)>> = costs.iter().map(category_amounts).collect();
+    let mut deltas = vec![0.0; costs.len()];
+    let all: Vec<usize> = (0..costs.len()).collect();
+
+    let applied = ordered
+        .into_iter()
+        .map(|adjustment| {
+            let targets: Vec<usize> = match &adjustment.item_id {
+                Some(item_id) => all.iter().copied().filter(|&i| &costs[i].item_id == item_id).collect(),
+                None => all.clone(),
+            };
+            AppliedAdjustment {
+                adjustment_id: adjustment.id.clone(),
+                item_id: adjustment.item_id.clone(),
+                kind: adjustment.kind,
+                amount_type: adjustment.amount_type,
+                value: adjustment.value,
+                category: adjustment.category.clone(),
+                description: adjustment.description.clone(),
+                amount_monthly: apply_one(adjustment, &mut amounts, &mut deltas, &targets),
+            }
+        })
+        .collect();
+
+    for (cost, delta) in costs.iter_mut().zip(deltas) {
+        cost.adjustment_monthly = delta;
+        cost.net_monthly = cost.cost.total_monthly + delta;
+    }
+
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::assert_close;
+
+    fn cost(item_id: &str, compute: f64, storage: f64) -> ItemCost {
+        let total = compute + storage;
+        ItemCost {
+            item_id: item_id.to_string(),
+            hostname: None,
+            section_id: None,
+            billing_mode: BillingMode::Monthly,
+            usage_hours_per_month: HOURS_PER_MONTH,
+            usage_schedule: None,
+            sku_category: None,
+            backup_vault_gb: 0.0,
+            cost: CostBreakdown {
+                compute_monthly: compute,
+                storage_monthly: storage,
+                total_monthly: total,
+                ..Default::default()
+            },
+            adjustment_monthly: 0.0,
+            net_monthly: total,
+            tax_monthly: 0.0,
+            gross_monthly: total,
+        }
+    }
+
+    fn adjustment(
+        id: &str,
+        item_id: Option<&str>,
+        kind: AdjustmentKind,
+        amount_type: AdjustmentType,
+        value: f64,
+    ) -> Adjustment {
+        Adjustment {
+            id: id.to_string(),
+            quote_id: "q".to_string(),
+            item_id: item_id.map(str::to_string),
+            kind,
+            amount_type,
+            value,
+            category: None,
+            description: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn percentages_apply_before_fixed_amounts() {
+        let mut costs = vec![cost("a", 100.0, 0.0)];
+        let adjustments = vec![
+            adjustment("fixed", None, AdjustmentKind::Discount, AdjustmentType::Fixed, 10.0),
+            adjustment("pct", None, AdjustmentKind::Discount, AdjustmentType::Percent, 10.0),
+        ];
+
+        let applied = apply_adjustments(&mut costs, &adjustments);
+
+        assert_eq!(applied[0].adjustment_id, "pct");
+        assert_close(applied[0].amount_monthly, -10.0);
+        assert_close(applied[1].amount_monthly, -10.0);
+        assert_close(costs[0].net_monthly, 80.0);
+    }
+
+    #[test]
+    fn item_adjustments_apply_before_quote_adjustments() {
+        let mut costs = vec![cost("a", 100.0, 0.0), cost("b", 100.0, 0.0)];
+        let adjustments = vec![
+            adjustment("quote", None, AdjustmentKind::Discount, AdjustmentType::Percent, 10.0),
+            adjustment("item", Some("a"), AdjustmentKind::Markup, AdjustmentType::Fixed, 20.0),
+        ];
+
+        apply_adjustments(&mut costs, &adjustments);
+
+        assert_close(costs[0].net_monthly, 108.0);
+        assert_close(costs[0].adjustment_monthly, 8.0);
+        assert_close(costs[1].net_monthly, 90.0);
+    }
+
+    #[test]
+    fn fixed_amounts_are_spread_in_proportion() {
+        let mut costs = vec![cost("a", 300.0, 0.0), cost("b", 100.0, 0.0)];
+        let adjustments = vec![adjustment("fixed", None, AdjustmentKind::Discount, AdjustmentType::Fixed, 40.0)];
+
+        apply_adjustments(&mut costs, &adjustments);
+
+        assert_close(costs[0].adjustment_monthly, -30.0);
+        assert_close(costs[1].adjustment_monthly, -10.0);
+    }
+
+    #[test]
+    fn discounts_never_go_below_zero() {
+        let mut costs = vec![cost("a", 100.0, 0.0)];
+        let adjustments = vec![
+            adjustment("big", None, AdjustmentKind::Discount, AdjustmentType::Percent, 150.0),
+            adjustment("more", None, AdjustmentKind::Discount, AdjustmentType::Fixed, 50.0),
+        ];
+
+        let applied = apply_adjustments(&mut costs, &adjustments);
+
+        assert_close(applied[0].amount_monthly, -100.0);
+        assert_close(applied[1].amount_monthly, 0.0);
+        assert_close(costs[0].net_monthly, 0.0);
+    }
+
+    #[test]
+    fn category_limits_the_adjusted_amount() {
+        let mut costs = vec![cost("a", 100.0, 40.0)];
+        let adjustments = vec![Adjustment {
+            category: Some("storage".to_string()),
+            ..adjustment("storage", None, AdjustmentKind::Discount, AdjustmentType::Percent, 50.0)
+        }];
+
+        apply_adjustments(&mut costs, &adjustments);
+
+        assert_close(costs[0].adjustment_monthly, -20.0);
+        assert_close(costs[0].net_monthly, 120.0);
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::db::Database;
+use crate::models::*;
+use crate::sizing::size_workloads;
+
+// Upper bound on the instances one expansion may create, across all components
+const MAX_INSTANCES: i32 = 200;
+
+// Load every .json, .yaml and .yml blueprint in the directory. Invalid files and
+// duplicate ids are logged and skipped so one bad file doesn't stop the API.
+pub fn load_blueprints(dir: &str) -> Vec<Blueprint> {
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("json" | "yaml" | "yml")))
+            .collect(),
+        Err(e) => {
+            log::warn!("No blueprints loaded from {}: {}", dir, e);
+            return Vec::new();
+        }
+    };
+    paths.sort();
+
+    let mut blueprints: Vec<Blueprint> = Vec::new();
+    for path in paths {
+        match parse_blueprint(&path).and_then(|blueprint| validate(&blueprint).map(|_| blueprint)) {
+            Ok(blueprint) if blueprints.iter().any(|b| b.id == blueprint.id) => {
+                log::error!("Skipping blueprint {}: duplicate id '{}'", path.display(), blueprint.id)
+            }
+            Ok(blueprint) => blueprints.push(blueprint),
+            Err(e) => log::error!("Skipping blueprint {}: {}", path.display(), e),
+        }
+    }
+
+    log::info!("Loaded {} blueprints from {}", blueprints.len(), dir);
+    blueprints
+}
+
+fn parse_blueprint(path: &Path) -> Result<Blueprint, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => serde_json::from_str(&text).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str(&text).map_err(|e| e.to_string()),
+    }
+}
+
+// Structural checks, then a trial expansion with the default parameter values
+fn validate(blueprint: &Blueprint) -> Result<(), String> {
+    if blueprint.id.trim().is_empty() || blueprint.name.trim().is_empty() {
+        return Err("id and name are required".to_string());
+    }
+    if blueprint.components.is_empty() {
+        return Err("at least one component is required".to_string());
+    }
+
+    let mut names = HashSet::new();
+    for param in &blueprint.parameters {
+        if !names.insert(param.name.as_str()) {
+            return Err(format!("duplicate parameter '{}'", param.name));
+        }
+        if let (Some(min), Some(max)) = (param.min, param.max) {
+            if min > max {
+                return Err(format!("parameter '{}' has min above max", param.name));
+            }
+        }
+    }
+
+    for component in &blueprint.components {
+        if component.name.trim().is_empty() {
+            return Err("every component needs a name".to_string());
+        }
+        let values = [component.count.as_ref(), Some(&component.vcpus), Some(&component.ram_gb), component.disk_gb.as_ref()];
+        for value in values.into_iter().flatten() {
+            if let BlueprintValue::Param(name) = value {
+                if !names.contains(name.as_str()) {
+                    return Err(format!("component '{}' uses unknown parameter '{}'", component.name, name));
+                }
+            }
+        }
+        if let Some(family) = &component.family {
+            if !FLAVOR_FAMILIES.contains(&family.as_str()) {
+                return Err(format!("component '{}' has unknown flavor family '{}'", component.name, family));
+            }
+        }
+        if component.disk_type.is_some() != component.disk_gb.is_some() {
+            return Err(format!("component '{}' needs both disk_type and disk_gb", component.name));
+        }
+    }
+
+    let defaults = resolve_parameters(blueprint, &BTreeMap::new())?;
+    sizing_requests(blueprint, &defaults, None, None).map(|_| ())
+}
+
+// Requested parameter values over the defaults; unknown names and values outside
+// a parameter's range are rejected
+pub fn resolve_parameters(blueprint: &Blueprint, given: &BTreeMap<String, f64>) -> Result<BTreeMap<String, f64>, String> {
+    if let Some(name) = given.keys().find(|name| !blueprint.parameters.iter().any(|p| &p.name == *name)) {
+        return Err(format!("unknown parameter '{}'", name));
+    }
+
+    blueprint
+        .parameters
+        .iter()
+        .map(|param| {
+            let value = given.get(&param.name).copied().unwrap_or(param.default);
+            if param.min.is_some_and(|min| value < min) || param.max.is_some_and(|max| value > max) {
+                let bounds = match (param.min, param.max) {
+                    (Some(min), Some(max)) => format!("between {} and {}", min, max),
+                    (Some(min), None) => format!("at least {}", min),
+                    _ => format!("at most {}", param.max.unwrap_or_default()),
+                };
+                return Err(format!("parameter '{}' must be {}, got {}", param.name, bounds, value));
+            }
+            Ok((param.name.clone(), value))
+        })
+        .collect()
+}
+
+fn value_of(value: &BlueprintValue, params: &BTreeMap<String, f64>) -> f64 {
+    match value {
+        BlueprintValue::Number(n) => *n,
+        // Parameter names are checked when the blueprint is loaded
+        BlueprintValue::Param(name) => params.get(name).copied().unwrap_or_default(),
+    }
+}
+
+// Whole-number value of at least `min`, for counts, vCPUs and disk sizes
+fn whole_value(
+    value: &BlueprintValue,
+    params: &BTreeMap<String, f64>,
+    min: i32,
+    field: &str,
+    component: &BlueprintComponent,
+) -> Result<i32, String> {
+    let n = value_of(value, params);
+    if n.fract() != 0.0 || n < min as f64 || n > i32::MAX as f64 {
+        return Err(format!(
+            "{} of component '{}' must be a whole number of at least {}, got {}",
+            field, component.name, min, n
+        ));
+    }
+    Ok(n as i32)
+}
+
+// One sizing request per component, with parameter values substituted
+pub fn sizing_requests<'a>(
+    blueprint: &'a Blueprint,
+    params: &BTreeMap<String, f64>,
+    billing_mode: Option<BillingMode>,
+    max_overshoot: Option<f64>,
+) -> Result<Vec<(&'a BlueprintComponent, SizingRequest)>, String> {
+    let mut instances = 0;
+    blueprint
+        .components
+        .iter()
+        .map(|component| {
+            let count = match &component.count {
+                Some(count) => whole_value(count, params, 0, "count", component)?,
+                None => 1,
+            };
+            instances += count;
+            if instances > MAX_INSTANCES {
+                return Err(format!("blueprint would create more than {} instances", MAX_INSTANCES));
+            }
+            let vcpus = whole_value(&component.vcpus, params, 1, "vcpus", component)?;
+            let ram_gb = value_of(&component.ram_gb, params);
+            if ram_gb <= 0.0 {
+                return Err(format!("ram_gb of component '{}' must be positive, got {}", component.name, ram_gb));
+            }
+            let disk_gb = component
+                .disk_gb
+                .as_ref()
+                .map(|gb| whole_value(gb, params, 1, "disk_gb", component))
+                .transpose()?;
+
+            let hostname = component
+                .hostname
+                .clone()
+                .unwrap_or_else(|| format!("{}-{{n}}", component.name));
+            let workloads = (1..=count)
+                .map(|n| WorkloadRequirement {
+                    name: hostname.replace("{n}", &n.to_string()),
+                    vcpus,
+                    ram_gb,
+                    disk_gb,
+                    disk_tier: component.disk_type.clone(),
+                })
+                .collect();
+
+            Ok((
+                component,
+                SizingRequest {
+                    workloads,
+                    family: component.family.clone(),
+                    billing_mode,
+                    max_overshoot,
+                    create_quote: None,
+                },
+            ))
+        })
+        .collect()
+}
+
+// Size every component with the best-matching flavors; component descriptions
+// carry over to the sized items
+pub fn expand(
+    db: &Database,
+    requests: &[(&BlueprintComponent, SizingRequest)],
+) -> rusqlite::Result<(Vec<SizedWorkload>, SizingTotals)> {
+    let mut items = Vec::new();
+    let mut totals = SizingTotals::default();
+
+    for (component, request) in requests {
+        let (mut sized, component_totals) = size_workloads(db, request)?;
+        for workload in &mut sized {
+            if let Some(item) = &mut workload.item {
+                item.description = component.description.clone();
+            }
+        }
+        items.extend(sized);
+        totals += component_totals;
+    }
+
+    Ok((items, totals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blueprint() -> Blueprint {
+        serde_json::from_value(serde_json::json!({
+            "id": "web",
+            "name": "Web",
+            "parameters": [
+                { "name": "nodes", "default": 2, "min": 1, "max": 10 },
+                { "name": "disk_gb", "default": 50, "min": 20 },
+            ],
+            "components": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn missing_parameters_take_their_defaults() {
+        let given = BTreeMap::from([("nodes".to_string(), 4.0)]);
+
+        let params = resolve_parameters(&blueprint(), &given).unwrap();
+
+        assert_eq!(params, BTreeMap::from([("disk_gb".to_string(), 50.0), ("nodes".to_string(), 4.0)]));
+    }
+
+    #[test]
+    fn unknown_parameters_are_rejected() {
+        let given = BTreeMap::from([("replicas".to_string(), 3.0)]);
+
+        assert_eq!(resolve_parameters(&blueprint(), &given).unwrap_err(), "unknown parameter 'replicas'");
+    }
+
+    #[test]
+    fn values_outside_the_range_are_rejected() {
+        let too_many = BTreeMap::from([("nodes".to_string(), 11.0)]);
+        let too_small = BTreeMap::from([("disk_gb".to_string(), 10.0)]);
+
+        assert_eq!(
+            resolve_parameters(&blueprint(), &too_many).unwrap_err(),
+            "parameter 'nodes' must be between 1 and 10, got 11"
+        );
+        assert_eq!(
+            resolve_parameters(&blueprint(), &too_small).unwrap_err(),
+            "parameter 'disk_gb' must be at least 20, got 10"
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use cron::Schedule;
+use std::str::FromStr;
+use std::time::Duration;
+
+// Runtime configuration, read from environment variables at startup
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub db_path: String,
+    pub crawler_url: String,
+    pub crawler_timeout: Duration,
+    pub crawl_schedule: Option<Schedule>,
+    pub blueprints_dir: String,
+    // Days deleted quotes and items stay in the trash; 0 keeps them forever
+    pub trash_retention_days: u32,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        // Default to /app/data for Docker
+        let db_path = std::env::var("DB_PATH").unwrap_or_else(|_| "/app/data/quotator.db".to_string());
+
+        let crawler_url = std::env::var("CRAWLER_URL")
+            .unwrap_or_else(|_| "http://localhost:3849".to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        let crawler_timeout = std::env::var("CRAWLER_TIMEOUT_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(30));
+
+        // Cron expression with seconds field, e.g. "0 0 3 * * *" for daily at 03:00 UTC.
+        // Unset or empty disables scheduled crawls.
+        let crawl_schedule = match std::env::var("CRAWL_SCHEDULE") {
+            Ok(expr) if !expr.trim().is_empty() => match Schedule::from_str(expr.trim()) {
+                Ok(schedule) => Some(schedule),
+                Err(e) => {
+                    log::error!("Invalid CRAWL_SCHEDULE '{}': {}", expr, e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        let blueprints_dir = std::env::var("BLUEPRINTS_DIR").unwrap_or_else(|_| "/app/blueprints".to_string());
+
+        let trash_retention_days = std::env::var("TRASH_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(30);
+
+        Config {
+            db_path,
+            crawler_url,
+            crawler_timeout,
+            crawl_schedule,
+            blueprints_dir,
+            trash_retention_days,
+        }
+    }
+
+    pub fn crawl_endpoint(&self) -> String {
+        format!("{}/crawl", self.crawler_url)
+    }
+
+    pub fn crawl_status_endpoint(&self) -> String {
+        format!("{}/status", self.crawler_url)
+    }
+}
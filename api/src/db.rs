@@ -1,4 +1,6 @@
-use rusqlite::{params, Connection, Result};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, params_from_iter, Connection, Result, Row, ToSql};
+use std::collections::HashMap;
 use std::path::Path;
 
 use crate::models::*;
@@ -7,6 +9,346 @@ pub struct Database {
     conn: Connection,
 }
 
+impl ToSql for BillingMode {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for BillingMode {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        BillingMode::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown billing mode '{}'", text).into()))
+    }
+}
+
+impl ToSql for PricingUnit {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for PricingUnit {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        PricingUnit::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown pricing unit '{}'", text).into()))
+    }
+}
+
+impl ToSql for AdjustmentKind {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for AdjustmentKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        AdjustmentKind::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown adjustment kind '{}'", text).into()))
+    }
+}
+
+impl ToSql for AdjustmentType {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for AdjustmentType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        AdjustmentType::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown adjustment type '{}'", text).into()))
+    }
+}
+
+impl ToSql for QuoteStatus {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for QuoteStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        QuoteStatus::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown quote status '{}'", text).into()))
+    }
+}
+
+impl ToSql for UsageSchedule {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for UsageSchedule {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        UsageSchedule::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown usage schedule '{}'", text).into()))
+    }
+}
+
+impl ToSql for AuditAction {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for AuditAction {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        AuditAction::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown audit action '{}'", text).into()))
+    }
+}
+
+impl ToSql for AuditEntity {
+    fn to_sql(&self) -> Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_str()))
+    }
+}
+
+impl FromSql for AuditEntity {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let text = value.as_str()?;
+        AuditEntity::parse(text).ok_or_else(|| FromSqlError::Other(format!("unknown audit entity '{}'", text).into()))
+    }
+}
+
+const ITEM_COLUMNS: &str = r#"id, quote_id, flavor_id, flavor_name, vcpus, ram_gb, flavor_price,
+               disk_type_id, disk_type_name, disk_size_gb, disk_price,
+               hostname, code_number, description, billing_mode, usage_hours_per_month,
+               usage_schedule, sku_id, sku_category, sku_name, sku_unit, sku_price, sku_quantity,
+               bandwidth_type_id, bandwidth_type_name, eip_count, bandwidth_mbps, traffic_gb, bandwidth_price,
+               os_image, os_image_name, os_licence_price,
+               backup_policy_id, backup_policy_name, backup_retention_days, backup_change_rate_pct, backup_price_per_gb,
+               section_id, position, created_at, updated_at, deleted_at, version"#;
+
+fn item_from_row(row: &Row) -> Result<QuoteItem> {
+    Ok(QuoteItem {
+        id: row.get(0)?,
+        quote_id: row.get(1)?,
+        flavor_id: row.get(2)?,
+        flavor_name: row.get(3)?,
+        vcpus: row.get(4)?,
+        ram_gb: row.get(5)?,
+        flavor_price: row.get(6)?,
+        disk_type_id: row.get(7)?,
+        disk_type_name: row.get(8)?,
+        disk_size_gb: row.get(9)?,
+        disk_price: row.get(10)?,
+        hostname: row.get(11)?,
+        code_number: row.get(12)?,
+        description: row.get(13)?,
+        billing_mode: row.get(14)?,
+        usage_hours_per_month: row.get(15)?,
+        usage_schedule: row.get(16)?,
+        sku_id: row.get(17)?,
+        sku_category: row.get(18)?,
+        sku_name: row.get(19)?,
+        sku_unit: row.get(20)?,
+        sku_price: row.get(21)?,
+        sku_quantity: row.get(22)?,
+        bandwidth_type_id: row.get(23)?,
+        bandwidth_type_name: row.get(24)?,
+        eip_count: row.get(25)?,
+        bandwidth_mbps: row.get(26)?,
+        traffic_gb: row.get(27)?,
+        bandwidth_price: row.get(28)?,
+        os_image: row.get(29)?,
+        os_image_name: row.get(30)?,
+        os_licence_price: row.get(31)?,
+        backup_policy_id: row.get(32)?,
+        backup_policy_name: row.get(33)?,
+        backup_retention_days: row.get(34)?,
+        backup_change_rate_pct: row.get(35)?,
+        backup_price_per_gb: row.get(36)?,
+        section_id: row.get(37)?,
+        position: row.get(38)?,
+        created_at: row.get(39)?,
+        updated_at: row.get(40)?,
+        deleted_at: row.get(41)?,
+        version: row.get(42)?,
+    })
+}
+
+fn backup_policy_from_row(row: &Row) -> Result<BackupPolicy> {
+    Ok(BackupPolicy {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        retention_days: row.get(2)?,
+        change_rate_pct: row.get(3)?,
+        price_per_gb: row.get(4)?,
+        region: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+const QUOTE_COLUMNS: &str = "id, name, customer_id, status, tax_profile_id, created_at, updated_at, deleted_at, version";
+
+fn quote_from_row(row: &Row) -> Result<Quote> {
+    Ok(Quote {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        customer_id: row.get(2)?,
+        status: row.get(3)?,
+        tax_profile_id: row.get(4)?,
+        created_at: row.get(5)?,
+        updated_at: row.get(6)?,
+        deleted_at: row.get(7)?,
+        version: row.get(8)?,
+    })
+}
+
+const CUSTOMER_COLUMNS: &str = r#"id, name, tax_id, contact_name, contact_email, contact_phone, currency,
+    default_discount_pct, created_at, updated_at"#;
+
+fn customer_from_row(row: &Row) -> Result<Customer> {
+    Ok(Customer {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        tax_id: row.get(2)?,
+        contact_name: row.get(3)?,
+        contact_email: row.get(4)?,
+        contact_phone: row.get(5)?,
+        currency: row.get(6)?,
+        default_discount_pct: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+fn tax_profile_from_row(row: &Row) -> Result<TaxProfile> {
+    Ok(TaxProfile {
+        id: row.get(0)?,
+        label: row.get(1)?,
+        rate: row.get(2)?,
+        inclusive: row.get(3)?,
+        created_at: row.get(4)?,
+    })
+}
+
+const SECTION_COLUMNS: &str = "id, quote_id, name, position, created_at, updated_at";
+
+fn section_from_row(row: &Row) -> Result<QuoteSection> {
+    Ok(QuoteSection {
+        id: row.get(0)?,
+        quote_id: row.get(1)?,
+        name: row.get(2)?,
+        position: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
+const COMMENT_COLUMNS: &str =
+    "id, quote_id, item_id, author, body, resolved, resolved_by, resolved_at, created_at, updated_at";
+
+fn comment_from_row(row: &Row) -> Result<Comment> {
+    Ok(Comment {
+        id: row.get(0)?,
+        quote_id: row.get(1)?,
+        item_id: row.get(2)?,
+        author: row.get(3)?,
+        body: row.get(4)?,
+        resolved: row.get(5)?,
+        resolved_by: row.get(6)?,
+        resolved_at: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+// Rows for the whole quote or an item that isn't in the trash; adjustments and
+// comments of trashed items come back when the item is restored
+const ON_LIVE_ITEM: &str = "(item_id IS NULL OR item_id IN (SELECT id FROM quote_items WHERE deleted_at IS NULL))";
+
+const ADJUSTMENT_COLUMNS: &str =
+    "id, quote_id, item_id, kind, amount_type, value, category, description, created_at";
+
+fn adjustment_from_row(row: &Row) -> Result<Adjustment> {
+    Ok(Adjustment {
+        id: row.get(0)?,
+        quote_id: row.get(1)?,
+        item_id: row.get(2)?,
+        kind: row.get(3)?,
+        amount_type: row.get(4)?,
+        value: row.get(5)?,
+        category: row.get(6)?,
+        description: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+const TEMPLATE_ITEM_COLUMNS: &str =
+    "flavor_id, disk_type_id, disk_size_gb, hostname_pattern, description, billing_mode, count";
+
+fn template_item_from_row(row: &Row) -> Result<TemplateItem> {
+    Ok(TemplateItem {
+        flavor_id: row.get(0)?,
+        disk_type_id: row.get(1)?,
+        disk_size_gb: row.get(2)?,
+        hostname_pattern: row.get(3)?,
+        description: row.get(4)?,
+        billing_mode: row.get(5)?,
+        count: row.get(6)?,
+    })
+}
+
+const AUDIT_COLUMNS: &str = "id, quote_id, entity, entity_id, action, before, after, actor, created_at";
+
+fn audit_from_row(row: &Row) -> Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        quote_id: row.get(1)?,
+        entity: row.get(2)?,
+        entity_id: row.get(3)?,
+        action: row.get(4)?,
+        before: row.get(5)?,
+        after: row.get(6)?,
+        actor: row.get(7)?,
+        created_at: row.get(8)?,
+    })
+}
+
+const JOURNAL_COLUMNS: &str = "id, quote_id, seq, item_id, before, after, undone, created_at, group_id";
+
+// Undo steps kept per quote; older ones are pruned as new changes are journaled
+const JOURNAL_LIMIT: u32 = 100;
+
+// Item snapshot stored as JSON
+fn item_snapshot(row: &Row, idx: usize) -> Result<Option<QuoteItem>> {
+    let value: Option<serde_json::Value> = row.get(idx)?;
+    value
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+fn journal_from_row(row: &Row) -> Result<JournalEntry> {
+    Ok(JournalEntry {
+        id: row.get(0)?,
+        quote_id: row.get(1)?,
+        seq: row.get(2)?,
+        item_id: row.get(3)?,
+        before: item_snapshot(row, 4)?,
+        after: item_snapshot(row, 5)?,
+        undone: row.get(6)?,
+        created_at: row.get(7)?,
+        group_id: row.get(8)?,
+    })
+}
+
+fn product_from_row(row: &Row) -> Result<Product> {
+    Ok(Product {
+        id: row.get(0)?,
+        category: row.get(1)?,
+        name: row.get(2)?,
+        unit: row.get(3)?,
+        price: row.get(4)?,
+        region: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
 impl Database {
     pub fn new(db_path: &str) -> Result<Self> {
         if let Some(parent) = Path::new(db_path).parent() {
@@ -43,6 +385,25 @@ impl Database {
                 created_at TEXT NOT NULL
             );
 
+            CREATE TABLE IF NOT EXISTS bandwidth_types (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                price_per_ip_month REAL NOT NULL,
+                price_per_mbps REAL NOT NULL,
+                price_per_gb REAL NOT NULL,
+                region TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS images (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                os_family TEXT NOT NULL,
+                price_per_vcpu_hour REAL NOT NULL,
+                region TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
             CREATE TABLE IF NOT EXISTS quotes (
                 id TEXT PRIMARY KEY,
                 name TEXT NOT NULL,
@@ -71,11 +432,214 @@ impl Database {
             );
 
             CREATE INDEX IF NOT EXISTS idx_quote_items_quote_id ON quote_items(quote_id);
+
+            CREATE TABLE IF NOT EXISTS products (
+                id TEXT PRIMARY KEY,
+                category TEXT NOT NULL,
+                name TEXT NOT NULL,
+                unit TEXT NOT NULL,
+                price REAL NOT NULL,
+                region TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_products_category ON products(category);
+
+            CREATE TABLE IF NOT EXISTS customers (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                tax_id TEXT,
+                contact_name TEXT,
+                contact_email TEXT,
+                contact_phone TEXT,
+                currency TEXT NOT NULL DEFAULT 'USD',
+                default_discount_pct REAL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS tax_profiles (
+                id TEXT PRIMARY KEY,
+                label TEXT NOT NULL,
+                rate REAL NOT NULL,
+                inclusive INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS quote_sections (
+                id TEXT PRIMARY KEY,
+                quote_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL,
+                FOREIGN KEY (quote_id) REFERENCES quotes(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_quote_sections_quote_id ON quote_sections(quote_id);
+
+            CREATE TABLE IF NOT EXISTS adjustments (
+                id TEXT PRIMARY KEY,
+                quote_id TEXT NOT NULL,
+                item_id TEXT,
+                kind TEXT NOT NULL,
+                amount_type TEXT NOT NULL,
+                value REAL NOT NULL,
+                category TEXT,
+                description TEXT,
+                created_at TEXT NOT NULL,
+                FOREIGN KEY (quote_id) REFERENCES quotes(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_adjustments_quote_id ON adjustments(quote_id);
+
+            CREATE TABLE IF NOT EXISTS quote_journal (
+                id TEXT PRIMARY KEY,
+                quote_id TEXT NOT NULL,
+                seq INTEGER NOT NULL,
+                item_id TEXT NOT NULL,
+                before TEXT,
+                after TEXT,
+                undone INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_quote_journal_quote_id ON quote_journal(quote_id, seq);
+
+            CREATE TABLE IF NOT EXISTS comments (
+                id TEXT PRIMARY KEY,
+                quote_id TEXT NOT NULL,
+                item_id TEXT,
+                author TEXT NOT NULL,
+                body TEXT NOT NULL,
+                resolved INTEGER NOT NULL DEFAULT 0,
+                resolved_by TEXT,
+                resolved_at TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_comments_quote_id ON comments(quote_id);
+
+            CREATE TABLE IF NOT EXISTS audit_log (
+                id TEXT PRIMARY KEY,
+                quote_id TEXT NOT NULL,
+                entity TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                before TEXT,
+                after TEXT,
+                actor TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_audit_log_quote_id ON audit_log(quote_id, created_at);
+
+            CREATE TABLE IF NOT EXISTS templates (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS template_items (
+                id TEXT PRIMARY KEY,
+                template_id TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                flavor_id TEXT,
+                disk_type_id TEXT,
+                disk_size_gb INTEGER,
+                hostname_pattern TEXT,
+                description TEXT,
+                billing_mode TEXT,
+                count INTEGER,
+                FOREIGN KEY (template_id) REFERENCES templates(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_template_items_template_id ON template_items(template_id);
+
+            CREATE TABLE IF NOT EXISTS backup_policies (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                retention_days INTEGER NOT NULL,
+                change_rate_pct REAL NOT NULL,
+                price_per_gb REAL NOT NULL,
+                region TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
             "#,
         )?;
+
+        // Columns added after the initial schema
+        self.add_column_if_missing("quote_items", "billing_mode", "TEXT NOT NULL DEFAULT 'monthly'")?;
+        self.add_column_if_missing("quote_items", "usage_hours_per_month", "REAL")?;
+        self.add_column_if_missing("quote_items", "usage_schedule", "TEXT")?;
+        self.add_column_if_missing("quote_items", "sku_id", "TEXT")?;
+        self.add_column_if_missing("quote_items", "sku_category", "TEXT")?;
+        self.add_column_if_missing("quote_items", "sku_name", "TEXT")?;
+        self.add_column_if_missing("quote_items", "sku_unit", "TEXT")?;
+        self.add_column_if_missing("quote_items", "sku_price", "REAL")?;
+        self.add_column_if_missing("quote_items", "sku_quantity", "REAL")?;
+        self.add_column_if_missing("quote_items", "bandwidth_type_id", "TEXT")?;
+        self.add_column_if_missing("quote_items", "bandwidth_type_name", "TEXT")?;
+        self.add_column_if_missing("quote_items", "eip_count", "INTEGER")?;
+        self.add_column_if_missing("quote_items", "bandwidth_mbps", "REAL")?;
+        self.add_column_if_missing("quote_items", "traffic_gb", "REAL")?;
+        self.add_column_if_missing("quote_items", "bandwidth_price", "REAL")?;
+        self.add_column_if_missing("quote_items", "os_image", "TEXT")?;
+        self.add_column_if_missing("quote_items", "os_image_name", "TEXT")?;
+        self.add_column_if_missing("quote_items", "os_licence_price", "REAL")?;
+        self.add_column_if_missing("quotes", "tax_profile_id", "TEXT")?;
+        self.add_column_if_missing("quotes", "customer_id", "TEXT")?;
+        self.add_column_if_missing("quotes", "status", "TEXT NOT NULL DEFAULT 'draft'")?;
+        self.conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_quotes_customer_id ON quotes(customer_id);")?;
+        self.add_column_if_missing("quote_items", "section_id", "TEXT")?;
+        // Existing items keep created_at order: position ties fall back to it
+        self.add_column_if_missing("quote_items", "position", "INTEGER NOT NULL DEFAULT 0")?;
+        self.add_column_if_missing("quote_items", "backup_policy_id", "TEXT")?;
+        self.add_column_if_missing("quote_items", "backup_policy_name", "TEXT")?;
+        self.add_column_if_missing("quote_items", "backup_retention_days", "INTEGER")?;
+        self.add_column_if_missing("quote_items", "backup_change_rate_pct", "REAL")?;
+        self.add_column_if_missing("quote_items", "backup_price_per_gb", "REAL")?;
+        // Soft delete: rows with deleted_at set are in the trash until purged
+        self.add_column_if_missing("quotes", "deleted_at", "TEXT")?;
+        self.add_column_if_missing("quote_items", "deleted_at", "TEXT")?;
+        // Optimistic concurrency: compared with If-Match on updates
+        self.add_column_if_missing("quotes", "version", "INTEGER NOT NULL DEFAULT 1")?;
+        self.add_column_if_missing("quote_items", "version", "INTEGER NOT NULL DEFAULT 1")?;
+        // Entries of one bulk change share a group and are undone together
+        self.add_column_if_missing("quote_journal", "group_id", "TEXT")?;
+        self.conn.execute_batch("UPDATE quote_journal SET group_id = id WHERE group_id IS NULL;")?;
+        Ok(())
+    }
+
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<Result<Vec<_>>>()?
+            .iter()
+            .any(|name| name == column);
+
+        if !exists {
+            self.conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+        }
         Ok(())
     }
 
+    // Run `f` in a transaction; an error rolls back everything it wrote. Inside
+    // another transaction `f` joins it, and the outer one commits or rolls back.
+    pub fn transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if !self.conn.is_autocommit() {
+            return f();
+        }
+        let tx = self.conn.unchecked_transaction()?;
+        let value = f()?;
+        tx.commit()?;
+        Ok(value)
+    }
+
     // Flavor operations
     pub fn get_flavors(&self) -> Result<Vec<Flavor>> {
         let mut stmt = self.conn.prepare(
@@ -100,17 +664,12 @@ impl Database {
         Ok(flavors)
     }
 
-    // Find best matching flavors for given CPU/RAM requirements
-    pub fn find_best_match(&self, vcpus: i32, ram_gb: f64) -> Result<Vec<Flavor>> {
+    pub fn get_flavor(&self, id: &str) -> Result<Option<Flavor>> {
         let mut stmt = self.conn.prepare(
-            r#"SELECT id, name, vcpus, ram_gb, price_hourly, price_monthly, price_yearly_1, price_yearly_3, region, created_at
-               FROM flavors
-               WHERE vcpus >= ?1 AND ram_gb >= ?2
-               ORDER BY price_hourly ASC
-               LIMIT 5"#
+            "SELECT id, name, vcpus, ram_gb, price_hourly, price_monthly, price_yearly_1, price_yearly_3, region, created_at FROM flavors WHERE id = ?1"
         )?;
 
-        let flavors = stmt.query_map(params![vcpus, ram_gb], |row| {
+        let mut flavors = stmt.query_map([id], |row| {
             Ok(Flavor {
                 id: row.get(0)?,
                 name: row.get(1)?,
@@ -123,9 +682,75 @@ impl Database {
                 region: row.get(8)?,
                 created_at: row.get(9)?,
             })
-        })?.collect::<Result<Vec<_>>>()?;
+        })?;
 
-        Ok(flavors)
+        flavors.next().transpose()
+    }
+
+    // Find best matching flavors for given CPU/RAM requirements
+    pub fn find_best_match(&self, query: &BestMatchQuery) -> Result<Vec<FlavorMatch>> {
+        let mode = query.billing_mode.unwrap_or_default();
+        let limit = query.limit.unwrap_or(5).clamp(1, 50);
+        let family_pattern = query.family.as_ref().map(|f| format!("{}.%", f));
+        // Upper bounds from the overshoot tolerance; NULL means unbounded
+        let (max_vcpus, max_ram) = match query.max_overshoot {
+            Some(pct) => {
+                let factor = 1.0 + pct.max(0.0) / 100.0;
+                (Some(query.vcpus as f64 * factor), Some(query.ram_gb * factor))
+            }
+            None => (None, None),
+        };
+
+        let sql = format!(
+            r#"SELECT id, name, vcpus, ram_gb, price_hourly, price_monthly, price_yearly_1, price_yearly_3, region, created_at
+               FROM flavors
+               WHERE vcpus >= ?1 AND ram_gb >= ?2
+                 AND (?3 IS NULL OR name LIKE ?3)
+                 AND (?4 IS NULL OR vcpus <= ?4)
+                 AND (?5 IS NULL OR ram_gb <= ?5)
+               ORDER BY {} ASC, price_hourly ASC
+               LIMIT ?6"#,
+            mode.price_column()
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+
+        let flavors = stmt.query_map(
+            params![query.vcpus, query.ram_gb, family_pattern, max_vcpus, max_ram, limit],
+            |row| {
+                Ok(Flavor {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    vcpus: row.get(2)?,
+                    ram_gb: row.get(3)?,
+                    price_hourly: row.get(4)?,
+                    price_monthly: row.get(5)?,
+                    price_yearly_1: row.get(6)?,
+                    price_yearly_3: row.get(7)?,
+                    region: row.get(8)?,
+                    created_at: row.get(9)?,
+                })
+            },
+        )?.collect::<Result<Vec<_>>>()?;
+
+        let excess_pct = |actual: f64, requested: f64| {
+            if requested > 0.0 { (actual - requested) / requested * 100.0 } else { 0.0 }
+        };
+
+        Ok(flavors
+            .into_iter()
+            .map(|flavor| {
+                let excess_vcpus_pct = excess_pct(flavor.vcpus as f64, query.vcpus as f64);
+                let excess_ram_pct = excess_pct(flavor.ram_gb, query.ram_gb);
+                FlavorMatch {
+                    price: flavor.price_for(mode),
+                    billing_mode: mode,
+                    excess_vcpus_pct,
+                    excess_ram_pct,
+                    waste_score: (excess_vcpus_pct + excess_ram_pct) / 2.0,
+                    flavor,
+                }
+            })
+            .collect())
     }
 
     // Disk type operations
@@ -147,209 +772,586 @@ impl Database {
         Ok(disks)
     }
 
-    // Quote operations
-    pub fn get_quotes(&self) -> Result<Vec<Quote>> {
+    pub fn get_disk_type(&self, id: &str) -> Result<Option<DiskType>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, created_at, updated_at FROM quotes ORDER BY updated_at DESC"
+            "SELECT id, name, price_per_gb, region, created_at FROM disk_types WHERE id = ?1"
         )?;
 
-        let quotes = stmt.query_map([], |row| {
-            Ok(Quote {
+        let mut disks = stmt.query_map([id], |row| {
+            Ok(DiskType {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                price_per_gb: row.get(2)?,
+                region: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?;
+
+        disks.next().transpose()
+    }
+
+    // Bandwidth type operations
+    pub fn get_bandwidth_types(&self) -> Result<Vec<BandwidthType>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, price_per_ip_month, price_per_mbps, price_per_gb, region, created_at FROM bandwidth_types ORDER BY price_per_mbps"
+        )?;
+
+        let bandwidths = stmt.query_map([], |row| {
+            Ok(BandwidthType {
                 id: row.get(0)?,
                 name: row.get(1)?,
-                created_at: row.get(2)?,
-                updated_at: row.get(3)?,
+                price_per_ip_month: row.get(2)?,
+                price_per_mbps: row.get(3)?,
+                price_per_gb: row.get(4)?,
+                region: row.get(5)?,
+                created_at: row.get(6)?,
             })
         })?.collect::<Result<Vec<_>>>()?;
 
-        Ok(quotes)
+        Ok(bandwidths)
     }
 
-    pub fn get_quotes_paginated(
-        &self,
-        page: u32,
-        limit: u32,
-        sort_by: &str,
-        sort_order: &str,
-        search: Option<&str>,
-    ) -> Result<(Vec<Quote>, u32)> {
-        // Validate sort_by to prevent SQL injection
-        let valid_sort_fields = ["name", "created_at", "updated_at"];
-        let sort_field = if valid_sort_fields.contains(&sort_by) {
-            sort_by
-        } else {
-            "updated_at"
-        };
+    pub fn get_bandwidth_type(&self, id: &str) -> Result<Option<BandwidthType>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, price_per_ip_month, price_per_mbps, price_per_gb, region, created_at FROM bandwidth_types WHERE id = ?1"
+        )?;
 
-        let order = if sort_order.to_lowercase() == "asc" { "ASC" } else { "DESC" };
-        let offset = (page - 1) * limit;
+        let mut bandwidths = stmt.query_map([id], |row| {
+            Ok(BandwidthType {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                price_per_ip_month: row.get(2)?,
+                price_per_mbps: row.get(3)?,
+                price_per_gb: row.get(4)?,
+                region: row.get(5)?,
+                created_at: row.get(6)?,
+            })
+        })?;
 
-        // Build query based on search
-        let (count_query, data_query, search_param);
-        if let Some(term) = search {
-            search_param = format!("%{}%", term);
-            count_query = "SELECT COUNT(*) FROM quotes WHERE name LIKE ?1".to_string();
-            data_query = format!(
-                "SELECT id, name, created_at, updated_at FROM quotes WHERE name LIKE ?1 ORDER BY {} {} LIMIT ?2 OFFSET ?3",
-                sort_field, order
-            );
-        } else {
-            search_param = String::new();
-            count_query = "SELECT COUNT(*) FROM quotes".to_string();
-            data_query = format!(
-                "SELECT id, name, created_at, updated_at FROM quotes ORDER BY {} {} LIMIT ?1 OFFSET ?2",
-                sort_field, order
-            );
-        }
+        bandwidths.next().transpose()
+    }
 
-        // Get total count
-        let total: u32 = if search.is_some() {
-            self.conn.query_row(&count_query, [&search_param], |row| row.get(0))?
-        } else {
-            self.conn.query_row(&count_query, [], |row| row.get(0))?
-        };
+    // OS image operations
+    pub fn get_images(&self) -> Result<Vec<Image>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, os_family, price_per_vcpu_hour, region, created_at FROM images ORDER BY os_family, name"
+        )?;
 
-        // Get paginated data
-        let mut stmt = self.conn.prepare(&data_query)?;
-        let quotes = if search.is_some() {
-            stmt.query_map(params![&search_param, limit, offset], |row| {
-                Ok(Quote {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    created_at: row.get(2)?,
-                    updated_at: row.get(3)?,
-                })
-            })?.collect::<Result<Vec<_>>>()?
-        } else {
-            stmt.query_map(params![limit, offset], |row| {
-                Ok(Quote {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    created_at: row.get(2)?,
-                    updated_at: row.get(3)?,
-                })
-            })?.collect::<Result<Vec<_>>>()?
-        };
+        let images = stmt.query_map([], |row| {
+            Ok(Image {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                os_family: row.get(2)?,
+                price_per_vcpu_hour: row.get(3)?,
+                region: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?.collect::<Result<Vec<_>>>()?;
 
-        Ok((quotes, total))
+        Ok(images)
     }
 
-    pub fn get_quote(&self, id: &str) -> Result<Option<Quote>> {
+    pub fn get_image(&self, id: &str) -> Result<Option<Image>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, name, created_at, updated_at FROM quotes WHERE id = ?1"
+            "SELECT id, name, os_family, price_per_vcpu_hour, region, created_at FROM images WHERE id = ?1"
         )?;
 
-        let mut quotes = stmt.query_map([id], |row| {
-            Ok(Quote {
+        let mut images = stmt.query_map([id], |row| {
+            Ok(Image {
                 id: row.get(0)?,
                 name: row.get(1)?,
-                created_at: row.get(2)?,
-                updated_at: row.get(3)?,
+                os_family: row.get(2)?,
+                price_per_vcpu_hour: row.get(3)?,
+                region: row.get(4)?,
+                created_at: row.get(5)?,
             })
         })?;
 
-        Ok(quotes.next().transpose()?)
+        images.next().transpose()
+    }
+
+    // Product (SKU) catalog operations
+    pub fn get_products(&self, category: Option<&str>) -> Result<Vec<Product>> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT id, category, name, unit, price, region, created_at FROM products
+               WHERE ?1 IS NULL OR category = ?1
+               ORDER BY category, price"#
+        )?;
+
+        let products = stmt.query_map([category], product_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(products)
+    }
+
+    pub fn get_product(&self, id: &str) -> Result<Option<Product>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, category, name, unit, price, region, created_at FROM products WHERE id = ?1"
+        )?;
+
+        let mut products = stmt.query_map([id], product_from_row)?;
+
+        products.next().transpose()
+    }
+
+    pub fn create_product(&self, product: &CreateProduct) -> Result<Product> {
+        let id = product.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let now = chrono::Utc::now().to_rfc3339();
+        let region = product.region.clone().unwrap_or_else(|| "tr-istanbul-1".to_string());
+
+        self.conn.execute(
+            "INSERT INTO products (id, category, name, unit, price, region, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![&id, &product.category, &product.name, &product.unit, product.price, &region, &now],
+        )?;
+
+        Ok(Product {
+            id,
+            category: product.category.clone(),
+            name: product.name.clone(),
+            unit: product.unit,
+            price: product.price,
+            region,
+            created_at: now,
+        })
+    }
+
+    // False when no product has the id
+    pub fn update_product(&self, id: &str, product: &UpdateProduct) -> Result<bool> {
+        let updated = self.conn.execute(
+            r#"UPDATE products SET
+               category = COALESCE(?1, category),
+               name = COALESCE(?2, name),
+               unit = COALESCE(?3, unit),
+               price = COALESCE(?4, price),
+               region = COALESCE(?5, region)
+               WHERE id = ?6"#,
+            params![&product.category, &product.name, &product.unit, &product.price, &product.region, id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    // False when no product has the id
+    pub fn delete_product(&self, id: &str) -> Result<bool> {
+        let deleted = self.conn.execute("DELETE FROM products WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
+    }
+
+    // Backup policy operations
+    pub fn get_backup_policies(&self) -> Result<Vec<BackupPolicy>> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT id, name, retention_days, change_rate_pct, price_per_gb, region, created_at
+               FROM backup_policies ORDER BY retention_days, name"#
+        )?;
+
+        let policies = stmt.query_map([], backup_policy_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(policies)
+    }
+
+    pub fn get_backup_policy(&self, id: &str) -> Result<Option<BackupPolicy>> {
+        let mut stmt = self.conn.prepare(
+            r#"SELECT id, name, retention_days, change_rate_pct, price_per_gb, region, created_at
+               FROM backup_policies WHERE id = ?1"#
+        )?;
+
+        let mut policies = stmt.query_map([id], backup_policy_from_row)?;
+
+        policies.next().transpose()
+    }
+
+    pub fn create_backup_policy(&self, policy: &CreateBackupPolicy) -> Result<BackupPolicy> {
+        let id = policy.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let now = chrono::Utc::now().to_rfc3339();
+        let region = policy.region.clone().unwrap_or_else(|| "tr-istanbul-1".to_string());
+
+        self.conn.execute(
+            r#"INSERT INTO backup_policies (id, name, retention_days, change_rate_pct, price_per_gb, region, created_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+            params![&id, &policy.name, policy.retention_days, policy.change_rate_pct, policy.price_per_gb, &region, &now],
+        )?;
+
+        Ok(BackupPolicy {
+            id,
+            name: policy.name.clone(),
+            retention_days: policy.retention_days,
+            change_rate_pct: policy.change_rate_pct,
+            price_per_gb: policy.price_per_gb,
+            region,
+            created_at: now,
+        })
+    }
+
+    pub fn update_backup_policy(&self, id: &str, policy: &UpdateBackupPolicy) -> Result<()> {
+        self.conn.execute(
+            r#"UPDATE backup_policies SET
+               name = COALESCE(?1, name),
+               retention_days = COALESCE(?2, retention_days),
+               change_rate_pct = COALESCE(?3, change_rate_pct),
+               price_per_gb = COALESCE(?4, price_per_gb),
+               region = COALESCE(?5, region)
+               WHERE id = ?6"#,
+            params![
+                &policy.name, &policy.retention_days, &policy.change_rate_pct, &policy.price_per_gb,
+                &policy.region, id
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_backup_policy(&self, id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM backup_policies WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    // Customer operations
+    pub fn get_customers(&self) -> Result<Vec<Customer>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM customers ORDER BY name",
+            CUSTOMER_COLUMNS
+        ))?;
+
+        let customers = stmt.query_map([], customer_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(customers)
+    }
+
+    pub fn get_customer(&self, id: &str) -> Result<Option<Customer>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM customers WHERE id = ?1",
+            CUSTOMER_COLUMNS
+        ))?;
+
+        let mut customers = stmt.query_map([id], customer_from_row)?;
+
+        customers.next().transpose()
+    }
+
+    pub fn create_customer(&self, customer: &CreateCustomer) -> Result<Customer> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        let currency = customer.currency.clone().unwrap_or_else(|| "USD".to_string());
+
+        self.conn.execute(
+            r#"INSERT INTO customers (id, name, tax_id, contact_name, contact_email, contact_phone, currency,
+               default_discount_pct, created_at, updated_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
+            params![
+                &id, &customer.name, &customer.tax_id, &customer.contact_name, &customer.contact_email,
+                &customer.contact_phone, &currency, &customer.default_discount_pct, &now, &now
+            ],
+        )?;
+
+        self.get_customer(&id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    pub fn update_customer(&self, id: &str, customer: &UpdateCustomer) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            r#"UPDATE customers SET
+               name = COALESCE(?1, name),
+               tax_id = COALESCE(?2, tax_id),
+               contact_name = COALESCE(?3, contact_name),
+               contact_email = COALESCE(?4, contact_email),
+               contact_phone = COALESCE(?5, contact_phone),
+               currency = COALESCE(?6, currency),
+               default_discount_pct = COALESCE(?7, default_discount_pct),
+               updated_at = ?8
+               WHERE id = ?9"#,
+            params![
+                &customer.name, &customer.tax_id, &customer.contact_name, &customer.contact_email,
+                &customer.contact_phone, &customer.currency, &customer.default_discount_pct, &now, id
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Quotes of a deleted customer are kept, unassigned
+    pub fn delete_customer(&self, id: &str) -> Result<()> {
+        self.transaction(|| {
+            self.conn.execute("UPDATE quotes SET customer_id = NULL WHERE customer_id = ?1", [id])?;
+            self.conn.execute("DELETE FROM customers WHERE id = ?1", [id])?;
+            Ok(())
+        })
+    }
+
+    pub fn get_customer_quotes(&self, customer_id: &str) -> Result<Vec<Quote>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quotes WHERE customer_id = ?1 AND deleted_at IS NULL ORDER BY updated_at DESC",
+            QUOTE_COLUMNS
+        ))?;
+
+        let quotes = stmt.query_map([customer_id], quote_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(quotes)
+    }
+
+    // Tax profile operations
+    pub fn get_tax_profiles(&self) -> Result<Vec<TaxProfile>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, label, rate, inclusive, created_at FROM tax_profiles ORDER BY label"
+        )?;
+
+        let profiles = stmt.query_map([], tax_profile_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(profiles)
+    }
+
+    pub fn get_tax_profile(&self, id: &str) -> Result<Option<TaxProfile>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, label, rate, inclusive, created_at FROM tax_profiles WHERE id = ?1"
+        )?;
+
+        let mut profiles = stmt.query_map([id], tax_profile_from_row)?;
+
+        profiles.next().transpose()
+    }
+
+    pub fn create_tax_profile(&self, profile: &CreateTaxProfile) -> Result<TaxProfile> {
+        let id = profile.id.clone().unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let now = chrono::Utc::now().to_rfc3339();
+        let inclusive = profile.inclusive.unwrap_or(false);
+
+        self.conn.execute(
+            "INSERT INTO tax_profiles (id, label, rate, inclusive, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![&id, &profile.label, profile.rate, inclusive, &now],
+        )?;
+
+        Ok(TaxProfile {
+            id,
+            label: profile.label.clone(),
+            rate: profile.rate,
+            inclusive,
+            created_at: now,
+        })
+    }
+
+    pub fn update_tax_profile(&self, id: &str, profile: &UpdateTaxProfile) -> Result<()> {
+        self.conn.execute(
+            r#"UPDATE tax_profiles SET
+               label = COALESCE(?1, label),
+               rate = COALESCE(?2, rate),
+               inclusive = COALESCE(?3, inclusive)
+               WHERE id = ?4"#,
+            params![&profile.label, &profile.rate, &profile.inclusive, id],
+        )?;
+        Ok(())
+    }
+
+    // Quotes using a deleted profile fall back to no tax
+    pub fn delete_tax_profile(&self, id: &str) -> Result<()> {
+        self.transaction(|| {
+            self.conn.execute("UPDATE quotes SET tax_profile_id = NULL WHERE tax_profile_id = ?1", [id])?;
+            self.conn.execute("DELETE FROM tax_profiles WHERE id = ?1", [id])?;
+            Ok(())
+        })
+    }
+
+    // Quote operations
+    pub fn get_quotes(&self) -> Result<Vec<Quote>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quotes WHERE deleted_at IS NULL ORDER BY updated_at DESC",
+            QUOTE_COLUMNS
+        ))?;
+
+        let quotes = stmt.query_map([], quote_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(quotes)
     }
 
-    pub fn create_quote(&self, name: &str) -> Result<Quote> {
+    pub fn get_quotes_paginated(
+        &self,
+        page: u32,
+        limit: u32,
+        sort_by: &str,
+        sort_order: &str,
+        search: Option<&str>,
+        customer_id: Option<&str>,
+    ) -> Result<(Vec<Quote>, u32)> {
+        // Validate sort_by to prevent SQL injection
+        let valid_sort_fields = ["name", "created_at", "updated_at"];
+        let sort_field = if valid_sort_fields.contains(&sort_by) {
+            sort_by
+        } else {
+            "updated_at"
+        };
+
+        let order = if sort_order.to_lowercase() == "asc" { "ASC" } else { "DESC" };
+        let offset = (page - 1) * limit;
+
+        // Optional filters; a NULL parameter matches every quote
+        let search_param = search.map(|term| format!("%{}%", term));
+        let filter = "deleted_at IS NULL AND (?1 IS NULL OR name LIKE ?1) AND (?2 IS NULL OR customer_id = ?2)";
+
+        // Get total count
+        let total: u32 = self.conn.query_row(
+            &format!("SELECT COUNT(*) FROM quotes WHERE {}", filter),
+            params![&search_param, customer_id],
+            |row| row.get(0),
+        )?;
+
+        // Get paginated data
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quotes WHERE {} ORDER BY {} {} LIMIT ?3 OFFSET ?4",
+            QUOTE_COLUMNS, filter, sort_field, order
+        ))?;
+        let quotes = stmt
+            .query_map(params![&search_param, customer_id, limit, offset], quote_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((quotes, total))
+    }
+
+    pub fn get_quote(&self, id: &str) -> Result<Option<Quote>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quotes WHERE id = ?1 AND deleted_at IS NULL",
+            QUOTE_COLUMNS
+        ))?;
+
+        let mut quotes = stmt.query_map([id], quote_from_row)?;
+
+        quotes.next().transpose()
+    }
+
+    pub fn create_quote(&self, quote: &CreateQuote) -> Result<Quote> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
 
+        let status = quote.status.unwrap_or_default();
+
         self.conn.execute(
-            "INSERT INTO quotes (id, name, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
-            params![&id, name, &now, &now],
+            r#"INSERT INTO quotes (id, name, customer_id, status, tax_profile_id, created_at, updated_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+            params![&id, &quote.name, &quote.customer_id, &status, &quote.tax_profile_id, &now, &now],
         )?;
 
         Ok(Quote {
             id,
-            name: name.to_string(),
+            name: quote.name.clone(),
+            customer_id: quote.customer_id.clone(),
+            status,
+            tax_profile_id: quote.tax_profile_id.clone(),
             created_at: now.clone(),
             updated_at: now,
+            deleted_at: None,
+            version: 1,
+        })
+    }
+
+    // Create a quote and all of its items in a single transaction
+    pub fn create_quote_with_items(&self, quote: &CreateQuote, items: &[CreateQuoteItem]) -> Result<(Quote, Vec<QuoteItem>)> {
+        self.transaction(|| {
+            let quote = self.create_quote(quote)?;
+            let created = items
+                .iter()
+                .map(|item| self.create_item(&quote.id, item))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((quote, created))
         })
     }
 
-    pub fn update_quote(&self, id: &str, name: &str) -> Result<()> {
+    pub fn update_quote(&self, id: &str, quote: &UpdateQuote) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
         self.conn.execute(
-            "UPDATE quotes SET name = ?1, updated_at = ?2 WHERE id = ?3",
-            params![name, &now, id],
+            r#"UPDATE quotes SET
+               name = COALESCE(?1, name),
+               customer_id = CASE WHEN ?2 = '' THEN NULL ELSE COALESCE(?2, customer_id) END,
+               status = COALESCE(?3, status),
+               tax_profile_id = CASE WHEN ?4 = '' THEN NULL ELSE COALESCE(?4, tax_profile_id) END,
+               updated_at = ?5,
+               version = version + 1
+               WHERE id = ?6 AND deleted_at IS NULL"#,
+            params![&quote.name, &quote.customer_id, &quote.status, &quote.tax_profile_id, &now, id],
         )?;
         Ok(())
     }
 
+    // Move a quote to the trash; its items, sections and adjustments are kept for restore
     pub fn delete_quote(&self, id: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM quote_items WHERE quote_id = ?1", [id])?;
-        self.conn.execute("DELETE FROM quotes WHERE id = ?1", [id])?;
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE quotes SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![&now, id],
+        )?;
         Ok(())
     }
 
+    // Whether the quote exists but is in the trash
+    pub fn quote_in_trash(&self, id: &str) -> Result<bool> {
+        self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM quotes WHERE id = ?1 AND deleted_at IS NOT NULL)",
+            [id],
+            |row| row.get(0),
+        )
+    }
+
+    // Take a quote out of the trash; None when it isn't there
+    pub fn restore_quote(&self, id: &str) -> Result<Option<Quote>> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let restored = self.conn.execute(
+            r#"UPDATE quotes SET deleted_at = NULL, updated_at = ?1, version = version + 1
+               WHERE id = ?2 AND deleted_at IS NOT NULL"#,
+            params![&now, id],
+        )?;
+        if restored == 0 {
+            return Ok(None);
+        }
+        self.get_quote(id)
+    }
+
     // Quote item operations
     pub fn get_items(&self, quote_id: &str) -> Result<Vec<QuoteItem>> {
-        let mut stmt = self.conn.prepare(
-            r#"SELECT id, quote_id, flavor_id, flavor_name, vcpus, ram_gb, flavor_price,
-               disk_type_id, disk_type_name, disk_size_gb, disk_price,
-               hostname, code_number, description, created_at, updated_at
-               FROM quote_items WHERE quote_id = ?1 ORDER BY created_at"#
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quote_items WHERE quote_id = ?1 AND deleted_at IS NULL ORDER BY position, created_at",
+            ITEM_COLUMNS
+        ))?;
 
-        let items = stmt.query_map([quote_id], |row| {
-            Ok(QuoteItem {
-                id: row.get(0)?,
-                quote_id: row.get(1)?,
-                flavor_id: row.get(2)?,
-                flavor_name: row.get(3)?,
-                vcpus: row.get(4)?,
-                ram_gb: row.get(5)?,
-                flavor_price: row.get(6)?,
-                disk_type_id: row.get(7)?,
-                disk_type_name: row.get(8)?,
-                disk_size_gb: row.get(9)?,
-                disk_price: row.get(10)?,
-                hostname: row.get(11)?,
-                code_number: row.get(12)?,
-                description: row.get(13)?,
-                created_at: row.get(14)?,
-                updated_at: row.get(15)?,
-            })
-        })?.collect::<Result<Vec<_>>>()?;
+        let items = stmt.query_map([quote_id], item_from_row)?.collect::<Result<Vec<_>>>()?;
 
         Ok(items)
     }
 
+    pub fn get_item(&self, item_id: &str) -> Result<Option<QuoteItem>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quote_items WHERE id = ?1 AND deleted_at IS NULL",
+            ITEM_COLUMNS
+        ))?;
+
+        let mut items = stmt.query_map([item_id], item_from_row)?;
+
+        items.next().transpose()
+    }
+
     pub fn create_item(&self, quote_id: &str, item: &CreateQuoteItem) -> Result<QuoteItem> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().to_rfc3339();
+        let billing_mode = item.billing_mode.unwrap_or_default();
 
         self.conn.execute(
             r#"INSERT INTO quote_items (id, quote_id, flavor_id, flavor_name, vcpus, ram_gb, flavor_price,
                disk_type_id, disk_type_name, disk_size_gb, disk_price, hostname, code_number, description,
-               created_at, updated_at)
-               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)"#,
+               billing_mode, usage_hours_per_month, usage_schedule,
+               sku_id, sku_category, sku_name, sku_unit, sku_price, sku_quantity,
+               bandwidth_type_id, bandwidth_type_name, eip_count, bandwidth_mbps, traffic_gb, bandwidth_price,
+               os_image, os_image_name, os_licence_price,
+               backup_policy_id, backup_policy_name, backup_retention_days, backup_change_rate_pct, backup_price_per_gb,
+               section_id, position, created_at, updated_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+               ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29, ?30, ?31, ?32,
+               ?33, ?34, ?35, ?36, ?37, ?38,
+               (SELECT COALESCE(MAX(position), -1) + 1 FROM quote_items WHERE quote_id = ?2), ?39, ?40)"#,
             params![
                 &id, quote_id, &item.flavor_id, &item.flavor_name, &item.vcpus, &item.ram_gb, &item.flavor_price,
                 &item.disk_type_id, &item.disk_type_name, &item.disk_size_gb, &item.disk_price,
-                &item.hostname, &item.code_number, &item.description, &now, &now
+                &item.hostname, &item.code_number, &item.description,
+                &billing_mode, &item.usage_hours_per_month, &item.usage_schedule,
+                &item.sku_id, &item.sku.category, &item.sku.name, &item.sku.unit, &item.sku.price, &item.sku_quantity,
+                &item.bandwidth_type_id, &item.bandwidth_type_name, &item.eip_count, &item.bandwidth_mbps,
+                &item.traffic_gb, &item.bandwidth_price, &item.os_image, &item.os_image_name, &item.os_licence_price,
+                &item.backup_policy_id, &item.backup.policy_name, &item.backup.retention_days,
+                &item.backup.change_rate_pct, &item.backup.price_per_gb, &item.section_id, &now, &now
             ],
         )?;
 
-        Ok(QuoteItem {
-            id,
-            quote_id: quote_id.to_string(),
-            flavor_id: item.flavor_id.clone(),
-            flavor_name: item.flavor_name.clone(),
-            vcpus: item.vcpus,
-            ram_gb: item.ram_gb,
-            flavor_price: item.flavor_price,
-            disk_type_id: item.disk_type_id.clone(),
-            disk_type_name: item.disk_type_name.clone(),
-            disk_size_gb: item.disk_size_gb,
-            disk_price: item.disk_price,
-            hostname: item.hostname.clone(),
-            code_number: item.code_number.clone(),
-            description: item.description.clone(),
-            created_at: now.clone(),
-            updated_at: now,
-        })
+        self.get_item(&id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
     }
 
     pub fn update_item(&self, item_id: &str, item: &UpdateQuoteItem) -> Result<()> {
@@ -369,19 +1371,655 @@ impl Database {
                hostname = COALESCE(?10, hostname),
                code_number = COALESCE(?11, code_number),
                description = COALESCE(?12, description),
-               updated_at = ?13
-               WHERE id = ?14"#,
+               billing_mode = COALESCE(?13, billing_mode),
+               usage_hours_per_month = COALESCE(?14, usage_hours_per_month),
+               usage_schedule = COALESCE(?15, usage_schedule),
+               sku_id = COALESCE(?16, sku_id),
+               sku_category = COALESCE(?17, sku_category),
+               sku_name = COALESCE(?18, sku_name),
+               sku_unit = COALESCE(?19, sku_unit),
+               sku_price = COALESCE(?20, sku_price),
+               sku_quantity = COALESCE(?21, sku_quantity),
+               bandwidth_type_id = COALESCE(?22, bandwidth_type_id),
+               bandwidth_type_name = COALESCE(?23, bandwidth_type_name),
+               eip_count = COALESCE(?24, eip_count),
+               bandwidth_mbps = COALESCE(?25, bandwidth_mbps),
+               traffic_gb = COALESCE(?26, traffic_gb),
+               bandwidth_price = COALESCE(?27, bandwidth_price),
+               os_image = COALESCE(?28, os_image),
+               os_image_name = COALESCE(?29, os_image_name),
+               os_licence_price = COALESCE(?30, os_licence_price),
+               backup_policy_id = COALESCE(?31, backup_policy_id),
+               backup_policy_name = COALESCE(?32, backup_policy_name),
+               backup_retention_days = COALESCE(?33, backup_retention_days),
+               backup_change_rate_pct = COALESCE(?34, backup_change_rate_pct),
+               backup_price_per_gb = COALESCE(?35, backup_price_per_gb),
+               section_id = CASE WHEN ?36 = '' THEN NULL ELSE COALESCE(?36, section_id) END,
+               updated_at = ?37,
+               version = version + 1
+               WHERE id = ?38 AND deleted_at IS NULL"#,
             params![
                 &item.flavor_id, &item.flavor_name, &item.vcpus, &item.ram_gb, &item.flavor_price,
                 &item.disk_type_id, &item.disk_type_name, &item.disk_size_gb, &item.disk_price,
-                &item.hostname, &item.code_number, &item.description, &now, item_id
+                &item.hostname, &item.code_number, &item.description,
+                &item.billing_mode, &item.usage_hours_per_month, &item.usage_schedule,
+                &item.sku_id, &item.sku.category, &item.sku.name, &item.sku.unit, &item.sku.price, &item.sku_quantity,
+                &item.bandwidth_type_id, &item.bandwidth_type_name, &item.eip_count, &item.bandwidth_mbps,
+                &item.traffic_gb, &item.bandwidth_price, &item.os_image, &item.os_image_name, &item.os_licence_price,
+                &item.backup_policy_id, &item.backup.policy_name, &item.backup.retention_days,
+                &item.backup.change_rate_pct, &item.backup.price_per_gb, &item.section_id, &now, item_id
             ],
         )?;
         Ok(())
     }
 
+    // Rewrite item positions to match the given order in one transaction
+    pub fn reorder_items(&self, quote_id: &str, item_ids: &[String]) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.transaction(|| {
+            for (position, item_id) in item_ids.iter().enumerate() {
+                self.conn.execute(
+                    "UPDATE quote_items SET position = ?1, updated_at = ?2 WHERE id = ?3 AND quote_id = ?4",
+                    params![position as i32, &now, item_id, quote_id],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    // Move an item to the trash; its item-level adjustments are kept for restore
     pub fn delete_item(&self, item_id: &str) -> Result<()> {
-        self.conn.execute("DELETE FROM quote_items WHERE id = ?1", [item_id])?;
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE quote_items SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![&now, item_id],
+        )?;
+        Ok(())
+    }
+
+    // Take an item out of the trash; None when it isn't there
+    pub fn restore_item(&self, quote_id: &str, item_id: &str) -> Result<Option<QuoteItem>> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let restored = self.conn.execute(
+            r#"UPDATE quote_items SET deleted_at = NULL, updated_at = ?1, version = version + 1
+               WHERE id = ?2 AND quote_id = ?3 AND deleted_at IS NOT NULL"#,
+            params![&now, item_id, quote_id],
+        )?;
+        if restored == 0 {
+            return Ok(None);
+        }
+        self.get_item(item_id)
+    }
+
+    // Write an item snapshot back in place under a new version, or move the item to
+    // the trash for None. A section deleted since the snapshot leaves the item unsectioned.
+    pub fn put_item_state(&self, item_id: &str, state: Option<&QuoteItem>) -> Result<()> {
+        let Some(item) = state else {
+            return self.delete_item(item_id);
+        };
+
+        let mut placeholders: Vec<String> = (1..=42).map(|i| format!("?{}", i)).collect();
+        placeholders.push("(SELECT COALESCE(MAX(version), 0) + 1 FROM quote_items WHERE id = ?1)".to_string());
+        self.conn.execute(
+            &format!("INSERT OR REPLACE INTO quote_items ({}) VALUES ({})", ITEM_COLUMNS, placeholders.join(", ")),
+            params![
+                &item.id, &item.quote_id, &item.flavor_id, &item.flavor_name, &item.vcpus, &item.ram_gb, &item.flavor_price,
+                &item.disk_type_id, &item.disk_type_name, &item.disk_size_gb, &item.disk_price,
+                &item.hostname, &item.code_number, &item.description,
+                &item.billing_mode, &item.usage_hours_per_month, &item.usage_schedule,
+                &item.sku_id, &item.sku_category, &item.sku_name, &item.sku_unit, &item.sku_price, &item.sku_quantity,
+                &item.bandwidth_type_id, &item.bandwidth_type_name, &item.eip_count, &item.bandwidth_mbps,
+                &item.traffic_gb, &item.bandwidth_price, &item.os_image, &item.os_image_name, &item.os_licence_price,
+                &item.backup_policy_id, &item.backup_policy_name, &item.backup_retention_days,
+                &item.backup_change_rate_pct, &item.backup_price_per_gb, &item.section_id, &item.position,
+                &item.created_at, &item.updated_at, None::<String>
+            ],
+        )?;
+        self.conn.execute(
+            "UPDATE quote_items SET section_id = NULL WHERE id = ?1 AND section_id NOT IN (SELECT id FROM quote_sections)",
+            [item_id],
+        )?;
+        Ok(())
+    }
+
+    // Trash listing, most recently deleted first. Items of deleted quotes are
+    // restored with their quote, so only the quote is listed.
+    pub fn get_trash(&self) -> Result<Trash> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quotes WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+            QUOTE_COLUMNS
+        ))?;
+        let quotes = stmt.query_map([], quote_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            r#"SELECT {} FROM quote_items WHERE deleted_at IS NOT NULL
+               AND quote_id IN (SELECT id FROM quotes WHERE deleted_at IS NULL)
+               ORDER BY deleted_at DESC"#,
+            ITEM_COLUMNS
+        ))?;
+        let items = stmt.query_map([], item_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(Trash { quotes, items })
+    }
+
+    // Permanently remove quotes and items deleted before the cutoff, returning
+    // how many of each were purged
+    pub fn purge_trash(&self, cutoff: &str) -> Result<(usize, usize)> {
+        self.transaction(|| {
+            let expired_quotes = "SELECT id FROM quotes WHERE deleted_at IS NOT NULL AND deleted_at < ?1";
+            for table in ["adjustments", "quote_sections", "quote_items", "quote_journal", "comments"] {
+                self.conn.execute(
+                    &format!("DELETE FROM {} WHERE quote_id IN ({})", table, expired_quotes),
+                    [cutoff],
+                )?;
+            }
+            let quotes = self.conn.execute(
+                "DELETE FROM quotes WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+                [cutoff],
+            )?;
+
+            for table in ["adjustments", "quote_journal", "comments"] {
+                self.conn.execute(
+                    &format!(
+                        r#"DELETE FROM {} WHERE item_id IN
+                           (SELECT id FROM quote_items WHERE deleted_at IS NOT NULL AND deleted_at < ?1)"#,
+                        table
+                    ),
+                    [cutoff],
+                )?;
+            }
+            let items = self.conn.execute(
+                "DELETE FROM quote_items WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+                [cutoff],
+            )?;
+
+            Ok((quotes, items))
+        })
+    }
+
+    // Add items to a quote in a single transaction, in the given order
+    pub fn create_items(&self, quote_id: &str, items: &[CreateQuoteItem]) -> Result<Vec<QuoteItem>> {
+        self.transaction(|| {
+            items
+                .iter()
+                .map(|item| self.create_item(quote_id, item))
+                .collect::<Result<Vec<_>>>()
+        })
+    }
+
+    // Quote section operations
+    pub fn get_sections(&self, quote_id: &str) -> Result<Vec<QuoteSection>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quote_sections WHERE quote_id = ?1 ORDER BY position, created_at",
+            SECTION_COLUMNS
+        ))?;
+
+        let sections = stmt.query_map([quote_id], section_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(sections)
+    }
+
+    pub fn get_section(&self, section_id: &str) -> Result<Option<QuoteSection>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM quote_sections WHERE id = ?1",
+            SECTION_COLUMNS
+        ))?;
+
+        let mut sections = stmt.query_map([section_id], section_from_row)?;
+
+        sections.next().transpose()
+    }
+
+    // New sections go to the end of the quote
+    pub fn create_section(&self, quote_id: &str, section: &CreateSection) -> Result<QuoteSection> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            r#"INSERT INTO quote_sections (id, quote_id, name, position, created_at, updated_at)
+               VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(position), -1) + 1 FROM quote_sections WHERE quote_id = ?2), ?4, ?5)"#,
+            params![&id, quote_id, &section.name, &now, &now],
+        )?;
+
+        self.get_section(&id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    pub fn update_section(&self, section_id: &str, section: &UpdateSection) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE quote_sections SET name = COALESCE(?1, name), updated_at = ?2 WHERE id = ?3",
+            params![&section.name, &now, section_id],
+        )?;
+        Ok(())
+    }
+
+    // Items in a deleted section are kept, unsectioned
+    pub fn delete_section(&self, section_id: &str) -> Result<()> {
+        self.transaction(|| {
+            self.conn.execute("UPDATE quote_items SET section_id = NULL WHERE section_id = ?1", [section_id])?;
+            self.conn.execute("DELETE FROM quote_sections WHERE id = ?1", [section_id])?;
+            Ok(())
+        })
+    }
+
+    // Rewrite section positions to match the given order in one transaction
+    pub fn reorder_sections(&self, quote_id: &str, section_ids: &[String]) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.transaction(|| {
+            for (position, section_id) in section_ids.iter().enumerate() {
+                self.conn.execute(
+                    "UPDATE quote_sections SET position = ?1, updated_at = ?2 WHERE id = ?3 AND quote_id = ?4",
+                    params![position as i32, &now, section_id, quote_id],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    // Items, adjustments, sections and tax profile used to price a quote
+    pub fn get_quote_pricing(&self, quote: &Quote) -> Result<QuotePricing> {
+        let tax = match &quote.tax_profile_id {
+            Some(id) => self.get_tax_profile(id)?,
+            None => None,
+        };
+        Ok(QuotePricing {
+            items: self.get_items(&quote.id)?,
+            adjustments: self.get_adjustments(&quote.id)?,
+            sections: self.get_sections(&quote.id)?,
+            tax,
+        })
+    }
+
+    // Price adjustment operations
+    pub fn get_adjustments(&self, quote_id: &str) -> Result<Vec<Adjustment>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM adjustments WHERE quote_id = ?1 AND {} ORDER BY created_at",
+            ADJUSTMENT_COLUMNS, ON_LIVE_ITEM
+        ))?;
+
+        let adjustments = stmt.query_map([quote_id], adjustment_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(adjustments)
+    }
+
+    pub fn get_adjustment(&self, id: &str) -> Result<Option<Adjustment>> {
+        let mut stmt = self.conn.prepare(&format!("SELECT {} FROM adjustments WHERE id = ?1", ADJUSTMENT_COLUMNS))?;
+
+        let mut adjustments = stmt.query_map([id], adjustment_from_row)?;
+
+        adjustments.next().transpose()
+    }
+
+    pub fn create_adjustment(&self, quote_id: &str, adjustment: &CreateAdjustment) -> Result<Adjustment> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            r#"INSERT INTO adjustments (id, quote_id, item_id, kind, amount_type, value, category, description, created_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+            params![
+                &id, quote_id, &adjustment.item_id, &adjustment.kind, &adjustment.amount_type, adjustment.value,
+                &adjustment.category, &adjustment.description, &now
+            ],
+        )?;
+
+        Ok(Adjustment {
+            id,
+            quote_id: quote_id.to_string(),
+            item_id: adjustment.item_id.clone(),
+            kind: adjustment.kind,
+            amount_type: adjustment.amount_type,
+            value: adjustment.value,
+            category: adjustment.category.clone(),
+            description: adjustment.description.clone(),
+            created_at: now,
+        })
+    }
+
+    pub fn update_adjustment(&self, id: &str, adjustment: &UpdateAdjustment) -> Result<()> {
+        self.conn.execute(
+            r#"UPDATE adjustments SET
+               kind = COALESCE(?1, kind),
+               amount_type = COALESCE(?2, amount_type),
+               value = COALESCE(?3, value),
+               category = COALESCE(?4, category),
+               description = COALESCE(?5, description)
+               WHERE id = ?6"#,
+            params![
+                &adjustment.kind, &adjustment.amount_type, &adjustment.value, &adjustment.category,
+                &adjustment.description, id
+            ],
+        )?;
+        Ok(())
+    }
+
+    // False when the quote has no adjustment with the id
+    pub fn delete_adjustment(&self, quote_id: &str, id: &str) -> Result<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM adjustments WHERE quote_id = ?1 AND id = ?2",
+            params![quote_id, id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    // Template operations
+    pub fn get_templates(&self) -> Result<Vec<Template>> {
+        let mut stmt = self.conn.prepare("SELECT id FROM templates ORDER BY name")?;
+        let ids = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<Result<Vec<_>>>()?;
+
+        let mut templates = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(template) = self.get_template(&id)? {
+                templates.push(template);
+            }
+        }
+        Ok(templates)
+    }
+
+    pub fn get_template(&self, id: &str) -> Result<Option<Template>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, description, created_at, updated_at FROM templates WHERE id = ?1",
+        )?;
+        let mut rows = stmt.query_map([id], |row| {
+            Ok(Template {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                items: Vec::new(),
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?;
+
+        let Some(mut template) = rows.next().transpose()? else {
+            return Ok(None);
+        };
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM template_items WHERE template_id = ?1 ORDER BY position",
+            TEMPLATE_ITEM_COLUMNS
+        ))?;
+        template.items = stmt.query_map([id], template_item_from_row)?.collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(template))
+    }
+
+    fn insert_template_items(&self, template_id: &str, items: &[TemplateItem]) -> Result<()> {
+        for (position, item) in items.iter().enumerate() {
+            self.conn.execute(
+                &format!(
+                    "INSERT INTO template_items (id, template_id, position, {}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    TEMPLATE_ITEM_COLUMNS
+                ),
+                params![
+                    uuid::Uuid::new_v4().to_string(), template_id, position as i32,
+                    &item.flavor_id, &item.disk_type_id, &item.disk_size_gb, &item.hostname_pattern,
+                    &item.description, &item.billing_mode, &item.count
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn create_template(&self, template: &CreateTemplate) -> Result<Template> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            "INSERT INTO templates (id, name, description, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![&id, &template.name, &template.description, &now, &now],
+        )?;
+        self.insert_template_items(&id, &template.items)?;
+        tx.commit()?;
+
+        self.get_template(&id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    pub fn update_template(&self, id: &str, template: &UpdateTemplate) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+
+        let tx = self.conn.unchecked_transaction()?;
+        self.conn.execute(
+            r#"UPDATE templates SET
+               name = COALESCE(?1, name),
+               description = COALESCE(?2, description),
+               updated_at = ?3
+               WHERE id = ?4"#,
+            params![&template.name, &template.description, &now, id],
+        )?;
+        if let Some(items) = &template.items {
+            self.conn.execute("DELETE FROM template_items WHERE template_id = ?1", [id])?;
+            self.insert_template_items(id, items)?;
+        }
+        tx.commit()
+    }
+
+    pub fn delete_template(&self, id: &str) -> Result<()> {
+        self.transaction(|| {
+            self.conn.execute("DELETE FROM template_items WHERE template_id = ?1", [id])?;
+            self.conn.execute("DELETE FROM templates WHERE id = ?1", [id])?;
+            Ok(())
+        })
+    }
+
+    // Audit log operations
+    pub fn record_audit(&self, change: &AuditChange) -> Result<()> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            &format!("INSERT INTO audit_log ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)", AUDIT_COLUMNS),
+            params![
+                &id, &change.quote_id, &change.entity, &change.entity_id, &change.action,
+                &change.before, &change.after, &change.actor, &now
+            ],
+        )?;
+        Ok(())
+    }
+
+    // Newest entries first
+    pub fn get_audit_entries(&self, quote_id: &str, page: u32, limit: u32) -> Result<(Vec<AuditEntry>, u32)> {
+        let offset = (page - 1) * limit;
+
+        let total: u32 = self.conn.query_row(
+            "SELECT COUNT(*) FROM audit_log WHERE quote_id = ?1",
+            [quote_id],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM audit_log WHERE quote_id = ?1 ORDER BY created_at DESC, rowid DESC LIMIT ?2 OFFSET ?3",
+            AUDIT_COLUMNS
+        ))?;
+        let entries = stmt
+            .query_map(params![quote_id, limit, offset], audit_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((entries, total))
+    }
+
+    // Comment operations
+
+    // Oldest first, optionally only one item's or only (un)resolved comments
+    pub fn get_comments(&self, quote_id: &str, query: &CommentQuery) -> Result<Vec<Comment>> {
+        let mut stmt = self.conn.prepare(&format!(
+            r#"SELECT {} FROM comments WHERE quote_id = ?1 AND {}
+               AND (?2 IS NULL OR item_id = ?2) AND (?3 IS NULL OR resolved = ?3)
+               ORDER BY created_at, rowid"#,
+            COMMENT_COLUMNS, ON_LIVE_ITEM
+        ))?;
+
+        let comments = stmt
+            .query_map(params![quote_id, &query.item_id, &query.resolved], comment_from_row)?
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(comments)
+    }
+
+    pub fn get_comment(&self, comment_id: &str) -> Result<Option<Comment>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM comments WHERE id = ?1",
+            COMMENT_COLUMNS
+        ))?;
+
+        let mut comments = stmt.query_map([comment_id], comment_from_row)?;
+
+        comments.next().transpose()
+    }
+
+    pub fn create_comment(&self, quote_id: &str, author: &str, comment: &CreateComment) -> Result<Comment> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        self.conn.execute(
+            r#"INSERT INTO comments (id, quote_id, item_id, author, body, resolved, created_at, updated_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6, ?7)"#,
+            params![&id, quote_id, &comment.item_id, author, &comment.body, &now, &now],
+        )?;
+
+        self.get_comment(&id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)
+    }
+
+    // Resolving records who resolved it and when; reopening clears both
+    pub fn update_comment(&self, comment_id: &str, actor: &str, comment: &UpdateComment) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            r#"UPDATE comments SET
+               body = COALESCE(?1, body),
+               resolved_by = CASE WHEN ?2 IS NULL OR ?2 = resolved THEN resolved_by WHEN ?2 THEN ?3 END,
+               resolved_at = CASE WHEN ?2 IS NULL OR ?2 = resolved THEN resolved_at WHEN ?2 THEN ?4 END,
+               resolved = COALESCE(?2, resolved),
+               updated_at = ?4
+               WHERE id = ?5"#,
+            params![&comment.body, &comment.resolved, actor, &now, comment_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_comment(&self, comment_id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM comments WHERE id = ?1", [comment_id])?;
+        Ok(())
+    }
+
+    // Quotes for the quote lists, with total and unresolved comment counts.
+    // Only the listed quotes' comments are counted, a chunk of ids at a time to
+    // stay under SQLite's parameter limit.
+    pub fn with_comment_counts(&self, quotes: Vec<Quote>) -> Result<Vec<QuoteListEntry>> {
+        let mut counts = HashMap::new();
+        for chunk in quotes.chunks(500) {
+            let placeholders = vec!["?"; chunk.len()].join(", ");
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT quote_id, COUNT(*), SUM(resolved = 0) FROM comments WHERE quote_id IN ({}) AND {} GROUP BY quote_id",
+                placeholders, ON_LIVE_ITEM
+            ))?;
+            let rows = stmt.query_map(params_from_iter(chunk.iter().map(|quote| &quote.id)), |row| {
+                Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+            })?;
+            for row in rows {
+                let (quote_id, count): (String, (u32, u32)) = row?;
+                counts.insert(quote_id, count);
+            }
+        }
+
+        Ok(quotes
+            .into_iter()
+            .map(|quote| {
+                let (comment_count, open_comment_count) = counts.get(&quote.id).copied().unwrap_or_default();
+                QuoteListEntry { quote, comment_count, open_comment_count }
+            })
+            .collect())
+    }
+
+    // Undo/redo journal operations
+
+    // Record a new item change as part of a group; anything undone before it can
+    // no longer be redone, and the oldest groups beyond JOURNAL_LIMIT are pruned
+    pub fn push_journal(
+        &self,
+        group_id: &str,
+        quote_id: &str,
+        item_id: &str,
+        before: Option<&QuoteItem>,
+        after: Option<&QuoteItem>,
+    ) -> Result<()> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        self.conn.execute("DELETE FROM quote_journal WHERE quote_id = ?1 AND undone = 1", [quote_id])?;
+        self.conn.execute(
+            r#"INSERT INTO quote_journal (id, quote_id, seq, item_id, before, after, undone, created_at, group_id)
+               VALUES (?1, ?2, (SELECT COALESCE(MAX(seq), 0) + 1 FROM quote_journal WHERE quote_id = ?2), ?3, ?4, ?5, 0, ?6, ?7)"#,
+            params![
+                &id, quote_id, item_id,
+                before.map(|b| serde_json::json!(b)), after.map(|a| serde_json::json!(a)), &now, group_id
+            ],
+        )?;
+        self.conn.execute(
+            r#"DELETE FROM quote_journal WHERE quote_id = ?1 AND group_id NOT IN (
+                   SELECT group_id FROM quote_journal WHERE quote_id = ?1
+                   GROUP BY group_id ORDER BY MAX(seq) DESC LIMIT ?2
+               )"#,
+            params![quote_id, JOURNAL_LIMIT],
+        )?;
+        Ok(())
+    }
+
+    // Entries of the group the next undo (latest applied) or redo (earliest
+    // undone) steps over, in the order they are to be stepped
+    pub fn next_journal_group(&self, quote_id: &str, undo: bool) -> Result<Vec<JournalEntry>> {
+        let (undone, order) = if undo { (false, "DESC") } else { (true, "ASC") };
+        let mut stmt = self.conn.prepare(&format!(
+            r#"SELECT {columns} FROM quote_journal WHERE quote_id = ?1 AND undone = ?2 AND group_id = (
+                   SELECT group_id FROM quote_journal WHERE quote_id = ?1 AND undone = ?2 ORDER BY seq {order} LIMIT 1
+               )
+               ORDER BY seq {order}"#,
+            columns = JOURNAL_COLUMNS,
+            order = order
+        ))?;
+
+        let entries = stmt.query_map(params![quote_id, undone], journal_from_row)?;
+
+        entries.collect()
+    }
+
+    pub fn set_journal_undone(&self, id: &str, undone: bool) -> Result<()> {
+        self.conn.execute("UPDATE quote_journal SET undone = ?1 WHERE id = ?2", params![undone, id])?;
+        Ok(())
+    }
+
+    // Number of steps available to undo and to redo
+    pub fn journal_counts(&self, quote_id: &str) -> Result<(u32, u32)> {
+        self.conn.query_row(
+            r#"SELECT COUNT(DISTINCT CASE WHEN undone = 0 THEN group_id END),
+                      COUNT(DISTINCT CASE WHEN undone = 1 THEN group_id END)
+               FROM quote_journal WHERE quote_id = ?1"#,
+            [quote_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+}
+
+// The crawler owns the catalog tables; tests seed them directly
+#[cfg(test)]
+impl Database {
+    pub fn insert_flavor(&self, flavor: &Flavor) -> Result<()> {
+        self.conn.execute(
+            r#"INSERT INTO flavors (id, name, vcpus, ram_gb, price_hourly, price_monthly, price_yearly_1, price_yearly_3, region, created_at)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"#,
+            params![
+                &flavor.id, &flavor.name, flavor.vcpus, flavor.ram_gb, flavor.price_hourly, flavor.price_monthly,
+                flavor.price_yearly_1, flavor.price_yearly_3, &flavor.region, &flavor.created_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn insert_disk_type(&self, disk: &DiskType) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO disk_types (id, name, price_per_gb, region, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![&disk.id, &disk.name, disk.price_per_gb, &disk.region, &disk.created_at],
+        )?;
         Ok(())
     }
 }
@@ -0,0 +1,158 @@
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::web::Bytes;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+
+use crate::models::{Comment, Quote, QuoteItem};
+
+// Messages a subscriber may fall behind by before it is disconnected; browsers
+// reconnect an EventSource on their own
+const CLIENT_BUFFER: usize = 64;
+
+// A change sent to subscribers as a server-sent event. Events without a quote
+// go to every subscriber.
+pub struct Event {
+    name: &'static str,
+    quote_id: Option<String>,
+    data: serde_json::Value,
+}
+
+impl Event {
+    pub fn item_created(item: &QuoteItem) -> Self {
+        Self::for_quote("item_created", &item.quote_id, serde_json::json!(item))
+    }
+
+    pub fn item_updated(item: &QuoteItem) -> Self {
+        Self::for_quote("item_updated", &item.quote_id, serde_json::json!(item))
+    }
+
+    pub fn item_deleted(quote_id: &str, item_id: &str) -> Self {
+        Self::for_quote("item_deleted", quote_id, serde_json::json!({ "id": item_id }))
+    }
+
+    pub fn items_reordered(quote_id: &str, item_ids: &[String]) -> Self {
+        Self::for_quote("items_reordered", quote_id, serde_json::json!({ "item_ids": item_ids }))
+    }
+
+    // Rename, status, customer or tax profile change, or restore from the trash
+    pub fn quote_updated(quote: &Quote) -> Self {
+        Self::for_quote("quote_updated", &quote.id, serde_json::json!(quote))
+    }
+
+    // Sections or adjustments changed: anything but the items that the quote's
+    // totals depend on
+    pub fn totals_changed(quote_id: &str, source: &str) -> Self {
+        Self::for_quote("totals_changed", quote_id, serde_json::json!({ "id": quote_id, "source": source }))
+    }
+
+    pub fn quote_deleted(quote_id: &str) -> Self {
+        Self::for_quote("quote_deleted", quote_id, serde_json::json!({ "id": quote_id }))
+    }
+
+    pub fn comment_created(comment: &Comment) -> Self {
+        Self::for_quote("comment_created", &comment.quote_id, serde_json::json!(comment))
+    }
+
+    // Edited, resolved or reopened
+    pub fn comment_updated(comment: &Comment) -> Self {
+        Self::for_quote("comment_updated", &comment.quote_id, serde_json::json!(comment))
+    }
+
+    pub fn comment_deleted(quote_id: &str, comment_id: &str) -> Self {
+        Self::for_quote("comment_deleted", quote_id, serde_json::json!({ "id": comment_id }))
+    }
+
+    // The crawler accepted a crawl; prices change once crawl_completed follows
+    pub fn crawl_started(scheduled: bool) -> Self {
+        Self::global("crawl_started", serde_json::json!({ "scheduled": scheduled }))
+    }
+
+    pub fn crawl_completed(success: bool, message: &str, scheduled: bool) -> Self {
+        Self::global("crawl_completed", serde_json::json!({
+            "success": success,
+            "message": message,
+            "scheduled": scheduled,
+        }))
+    }
+
+    // Catalog prices or tax rates changed, from a crawl, a SKU edit or a tax profile edit
+    pub fn prices_updated(source: &str) -> Self {
+        Self::global("prices_updated", serde_json::json!({ "source": source }))
+    }
+
+    fn for_quote(name: &'static str, quote_id: &str, data: serde_json::Value) -> Self {
+        Self { name, quote_id: Some(quote_id.to_string()), data }
+    }
+
+    fn global(name: &'static str, data: serde_json::Value) -> Self {
+        Self { name, quote_id: None, data }
+    }
+
+    fn to_message(&self) -> Bytes {
+        Bytes::from(format!("event: {}\ndata: {}\n\n", self.name, self.data))
+    }
+}
+
+struct Subscriber {
+    // None for the global channel
+    quote_id: Option<String>,
+    sender: mpsc::Sender<Bytes>,
+}
+
+// Fan-out of events to connected SSE clients
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl EventBus {
+    // Stream of the quote's events, or of global events only for None. Quote
+    // subscribers also receive global events.
+    pub fn subscribe(&self, quote_id: Option<String>) -> EventStream {
+        let (sender, receiver) = mpsc::channel(CLIENT_BUFFER);
+        // Flushes the response headers so the client sees the connection open
+        let _ = sender.try_send(Bytes::from_static(b": connected\n\n"));
+        self.subscribers.lock().unwrap().push(Subscriber { quote_id, sender });
+        EventStream(receiver)
+    }
+
+    pub fn publish(&self, event: Event) {
+        let message = event.to_message();
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            let wanted = event.quote_id.is_none() || subscriber.quote_id == event.quote_id;
+            if wanted {
+                subscriber.sender.try_send(message.clone()).is_ok()
+            } else {
+                !subscriber.sender.is_closed()
+            }
+        });
+    }
+
+    // Comment line to every subscriber, so idle connections stay open through
+    // proxies and disconnected clients are dropped
+    pub fn keepalive(&self) {
+        let ping = Bytes::from_static(b": ping\n\n");
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.sender.try_send(ping.clone()).is_ok());
+    }
+}
+
+// Response body that stays open and yields events as they are published
+pub struct EventStream(mpsc::Receiver<Bytes>);
+
+impl MessageBody for EventStream {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        self.get_mut().0.poll_recv(cx).map(|message| message.map(Ok))
+    }
+}
@@ -0,0 +1,160 @@
+use crate::models::*;
+use crate::pricing::quote_totals;
+
+// Quote a CSV field, doubling embedded quotes
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+fn csv_row(fields: &[String]) -> String {
+    fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(",")
+}
+
+fn opt<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(|v| v.to_string()).unwrap_or_default()
+}
+
+// Monthly amount columns, shared by item rows and the total row
+const COST_HEADERS: [&str; 7] = [
+    "Compute Monthly ($)", "Licence Monthly ($)", "Storage Monthly ($)", "Backup Monthly ($)",
+    "Network Monthly ($)", "Service Monthly ($)", "Monthly ($)",
+];
+
+fn cost_fields(cost: &CostBreakdown) -> Vec<String> {
+    [
+        cost.compute_monthly,
+        cost.licence_monthly,
+        cost.storage_monthly,
+        cost.backup_monthly,
+        cost.network_monthly,
+        cost.service_monthly,
+        cost.total_monthly,
+    ]
+        .iter()
+        .map(|v| format!("{:.2}", v))
+        .collect()
+}
+
+// List price is in the cost columns; adjustments, net, tax and gross follow
+const NET_HEADERS: [&str; 4] = ["Adjustments ($)", "Net Monthly ($)", "Tax Monthly ($)", "Gross Monthly ($)"];
+
+fn net_fields(adjustment_monthly: f64, net_monthly: f64, tax_monthly: f64, gross_monthly: f64) -> Vec<String> {
+    [adjustment_monthly, net_monthly, tax_monthly, gross_monthly]
+        .iter()
+        .map(|v| format!("{:.2}", v))
+        .collect()
+}
+
+// CSV export of a quote, priced by the server-side pricing engine
+pub fn quote_csv(quote: &Quote, pricing: &QuotePricing) -> String {
+    let headers = [
+        "Section", "Hostname", "Code", "Instance Type", "vCPUs", "RAM (GB)", "OS Image", "Disk Type", "Disk (GB)",
+        "Backup Policy", "Vault (GB)",
+        "Bandwidth", "EIPs", "Mbps", "Traffic (GB)",
+        "Service", "Quantity", "Unit", "Description", "Billing Mode", "Hours/Month", "Hourly ($)",
+    ];
+
+    let header_row: Vec<String> = headers
+        .iter()
+        .chain(COST_HEADERS.iter())
+        .chain(NET_HEADERS.iter())
+        .map(|h| h.to_string())
+        .collect();
+    let mut lines = vec![csv_row(&header_row)];
+
+    let totals = quote_totals(&quote.id, pricing);
+
+    // Items grouped by section in section order, unsectioned items last
+    let section_index = |item: &QuoteItem| {
+        item.section_id
+            .as_ref()
+            .and_then(|id| pricing.sections.iter().position(|s| &s.id == id))
+            .unwrap_or(pricing.sections.len())
+    };
+    let mut rows: Vec<(&QuoteItem, &ItemCost)> = pricing.items.iter().zip(&totals.items).collect();
+    rows.sort_by_key(|(item, _)| section_index(item));
+
+    for (item, cost) in rows {
+        let section = pricing.sections.get(section_index(item)).map(|s| s.name.clone());
+        let mut fields = vec![
+            section.unwrap_or_default(),
+            opt(&item.hostname),
+            opt(&item.code_number),
+            opt(&item.flavor_name),
+            opt(&item.vcpus),
+            opt(&item.ram_gb),
+            opt(&item.os_image_name),
+            opt(&item.disk_type_name),
+            opt(&item.disk_size_gb),
+            opt(&item.backup_policy_name),
+            format!("{:.0}", cost.backup_vault_gb),
+            opt(&item.bandwidth_type_name),
+            opt(&item.eip_count),
+            opt(&item.bandwidth_mbps),
+            opt(&item.traffic_gb),
+            opt(&item.sku_name),
+            opt(&item.sku_quantity),
+            item.sku_unit.map(|u| u.as_str().to_string()).unwrap_or_default(),
+            opt(&item.description),
+            item.billing_mode.as_str().to_string(),
+            format!("{:.1}", cost.usage_hours_per_month),
+            format!("{:.4}", item.flavor_price.unwrap_or(0.0)),
+        ];
+        fields.extend(cost_fields(&cost.cost));
+        fields.extend(net_fields(cost.adjustment_monthly, cost.net_monthly, cost.tax_monthly, cost.gross_monthly));
+        lines.push(csv_row(&fields));
+    }
+
+    // Subtotal rows per section (when the quote has sections) and per billing mode, then the grand total
+    if !pricing.sections.is_empty() {
+        for section in &totals.by_section {
+            let mut section_row = vec![String::new(); headers.len()];
+            section_row[0] = format!("Subtotal ({})", section.name);
+            section_row.extend(cost_fields(&section.cost));
+            section_row.extend(net_fields(
+                section.adjustment_monthly,
+                section.net_monthly,
+                section.tax_monthly,
+                section.gross_monthly,
+            ));
+            lines.push(csv_row(&section_row));
+        }
+    }
+
+    for mode in &totals.by_mode {
+        let mut mode_row = vec![String::new(); headers.len()];
+        mode_row[0] = format!("Subtotal ({})", mode.billing_mode.as_str());
+        mode_row.extend(cost_fields(&mode.cost));
+        mode_row.extend(net_fields(mode.adjustment_monthly, mode.net_monthly, mode.tax_monthly, mode.gross_monthly));
+        lines.push(csv_row(&mode_row));
+    }
+
+    let mut total_row = vec![String::new(); headers.len()];
+    total_row[0] = "Total".to_string();
+    if let Some(profile) = &totals.tax_profile {
+        total_row[1] = format!(
+            "{} {}% ({})",
+            profile.label,
+            profile.rate,
+            if profile.inclusive { "inclusive" } else { "exclusive" }
+        );
+    }
+    total_row.extend(cost_fields(&totals.cost));
+    total_row.extend(net_fields(totals.adjustment_monthly, totals.net_monthly, totals.tax_monthly, totals.gross_monthly));
+    lines.push(csv_row(&total_row));
+
+    lines.join("\n")
+}
+
+// Download file name derived from the quote name
+pub fn csv_filename(quote: &Quote) -> String {
+    let name: String = quote
+        .name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("_")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        .collect();
+    format!("{}_quote.csv", name)
+}
@@ -1,6 +1,14 @@
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Responder};
 
 use crate::models::{*, ApiError, PaginatedResponse, PaginationQuery};
+use crate::blueprints::{expand, resolve_parameters, sizing_requests};
+use crate::export::{csv_filename, quote_csv};
+use crate::optimizer::optimize;
+use crate::pricing::{quote_totals, tco_report};
+use crate::scheduler::{run_crawl, CrawlGuard, CrawlOutcome};
+use crate::sizing::{rightsize_items, size_workloads};
+use crate::db::Database;
+use crate::events::{Event, EventStream};
 use crate::AppState;
 
 // Health check
@@ -62,268 +70,3027 @@ pub async fn get_all_pricing(data: web::Data<AppState>) -> impl Responder {
         }
     };
 
-    HttpResponse::Ok().json(PricingData { flavors, disk_types })
-}
+    let bandwidth_types = match db.get_bandwidth_types() {
+        Ok(b) => b,
+        Err(e) => {
+            log::error!("Failed to get bandwidth types: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch pricing data"
+            }));
+        }
+    };
 
-pub async fn trigger_crawl() -> impl Responder {
-    // Trigger the crawler service
-    let client = reqwest::Client::new();
+    let images = match db.get_images() {
+        Ok(i) => i,
+        Err(e) => {
+            log::error!("Failed to get images: {}", e);
+            return HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch pricing data"
+            }));
+        }
+    };
 
-    match client.post("http://localhost:3849/crawl")
-        .timeout(std::time::Duration::from_secs(30))
-        .send()
-        .await
-    {
-        Ok(resp) if resp.status().is_success() => {
-            HttpResponse::Ok().json(serde_json::json!({
-                "status": "ok",
-                "message": "Crawl triggered successfully"
+    HttpResponse::Ok().json(PricingData { flavors, disk_types, bandwidth_types, images })
+}
+
+pub async fn get_bandwidth_types(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_bandwidth_types() {
+        Ok(bandwidths) => HttpResponse::Ok().json(bandwidths),
+        Err(e) => {
+            log::error!("Failed to get bandwidth types: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch bandwidth types"
             }))
         }
-        Ok(resp) => {
-            log::warn!("Crawler returned error: {}", resp.status());
-            HttpResponse::Ok().json(serde_json::json!({
-                "status": "warning",
-                "message": "Crawler may not be running"
+    }
+}
+
+pub async fn get_images(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_images() {
+        Ok(images) => HttpResponse::Ok().json(images),
+        Err(e) => {
+            log::error!("Failed to get images: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch images"
             }))
         }
+    }
+}
+
+// Customer handlers
+pub async fn get_customers(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_customers() {
+        Ok(customers) => HttpResponse::Ok().json(customers),
         Err(e) => {
-            log::warn!("Failed to trigger crawler: {}", e);
-            HttpResponse::Ok().json(serde_json::json!({
-                "status": "warning",
-                "message": "Crawler service not available"
-            }))
+            log::error!("Failed to get customers: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch customers",
+                "CUSTOMERS_FETCH_ERROR"
+            ))
         }
     }
 }
 
-// Quote handlers
-pub async fn get_quotes(
+pub async fn get_customer(
     data: web::Data<AppState>,
-    query: web::Query<PaginationQuery>,
+    path: web::Path<String>,
 ) -> impl Responder {
+    let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    let page = query.page.unwrap_or(1).max(1);
-    let limit = query.limit.unwrap_or(100).min(100);
-    let sort_by = query.sort_by.as_deref().unwrap_or("updated_at");
-    let sort_order = query.sort_order.as_deref().unwrap_or("desc");
-    let search = query.search.as_deref();
-
-    match db.get_quotes_paginated(page, limit, sort_by, sort_order, search) {
-        Ok((quotes, total)) => {
-            let total_pages = ((total as f64) / (limit as f64)).ceil() as u32;
-            HttpResponse::Ok().json(PaginatedResponse {
-                data: quotes,
-                total,
-                page,
-                limit,
-                total_pages,
-            })
-        }
+    match db.get_customer(&id) {
+        Ok(Some(customer)) => HttpResponse::Ok().json(customer),
+        Ok(None) => HttpResponse::NotFound().json(ApiError::new(
+            "Customer not found",
+            "CUSTOMER_NOT_FOUND"
+        )),
         Err(e) => {
-            log::error!("Failed to get quotes: {}", e);
+            log::error!("Failed to get customer: {}", e);
             HttpResponse::InternalServerError().json(ApiError::new(
-                "Failed to fetch quotes",
-                "QUOTES_FETCH_ERROR"
+                "Failed to fetch customer",
+                "CUSTOMER_FETCH_ERROR"
             ))
         }
     }
 }
 
-// Legacy endpoint for backward compatibility (returns all quotes)
-pub async fn get_quotes_all(data: web::Data<AppState>) -> impl Responder {
+fn invalid_default_discount(pct: Option<f64>) -> Option<HttpResponse> {
+    match pct {
+        Some(pct) if !(0.0..=100.0).contains(&pct) => Some(HttpResponse::BadRequest().json(ApiError::new(
+            "Default discount must be between 0 and 100",
+            "INVALID_CUSTOMER"
+        ))),
+        _ => None,
+    }
+}
+
+pub async fn create_customer(
+    data: web::Data<AppState>,
+    body: web::Json<CreateCustomer>,
+) -> impl Responder {
+    if body.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Customer requires a name",
+            "INVALID_CUSTOMER"
+        ));
+    }
+    if let Some(resp) = invalid_default_discount(body.default_discount_pct) {
+        return resp;
+    }
+
     let db = data.db.lock().unwrap();
-    match db.get_quotes() {
-        Ok(quotes) => HttpResponse::Ok().json(quotes),
+    match db.create_customer(&body) {
+        Ok(customer) => HttpResponse::Created().json(customer),
         Err(e) => {
-            log::error!("Failed to get quotes: {}", e);
+            log::error!("Failed to create customer: {}", e);
             HttpResponse::InternalServerError().json(ApiError::new(
-                "Failed to fetch quotes",
-                "QUOTES_FETCH_ERROR"
+                "Failed to create customer",
+                "CUSTOMER_CREATE_ERROR"
             ))
         }
     }
 }
 
-pub async fn get_quote(
+pub async fn update_customer(
     data: web::Data<AppState>,
     path: web::Path<String>,
+    body: web::Json<UpdateCustomer>,
 ) -> impl Responder {
+    if let Some(resp) = invalid_default_discount(body.default_discount_pct) {
+        return resp;
+    }
+
     let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    match db.get_quote(&id) {
-        Ok(Some(quote)) => HttpResponse::Ok().json(quote),
-        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
-            "error": "Quote not found"
+    match db.update_customer(&id, &body) {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "ok"
         })),
         Err(e) => {
-            log::error!("Failed to get quote: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch quote"
-            }))
+            log::error!("Failed to update customer: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update customer",
+                "CUSTOMER_UPDATE_ERROR"
+            ))
         }
     }
 }
 
-pub async fn create_quote(
+pub async fn delete_customer(
     data: web::Data<AppState>,
-    body: web::Json<CreateQuote>,
+    path: web::Path<String>,
 ) -> impl Responder {
+    let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    match db.create_quote(&body.name) {
-        Ok(quote) => HttpResponse::Created().json(quote),
+    match db.delete_customer(&id) {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "ok"
+        })),
         Err(e) => {
-            log::error!("Failed to create quote: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to create quote"
-            }))
+            log::error!("Failed to delete customer: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete customer",
+                "CUSTOMER_DELETE_ERROR"
+            ))
         }
     }
 }
 
-pub async fn update_quote(
+// Quote values for a customer, with the total of quotes still open
+pub async fn get_customer_summary(
     data: web::Data<AppState>,
     path: web::Path<String>,
-    body: web::Json<UpdateQuote>,
 ) -> impl Responder {
     let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    if let Some(name) = &body.name {
-        match db.update_quote(&id, name) {
-            Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-                "status": "ok"
-            })),
-            Err(e) => {
-                log::error!("Failed to update quote: {}", e);
-                HttpResponse::InternalServerError().json(serde_json::json!({
-                    "error": "Failed to update quote"
-                }))
-            }
+    let customer = match db.get_customer(&id) {
+        Ok(Some(customer)) => customer,
+        Ok(None) => {
+            return HttpResponse::NotFound().json(ApiError::new(
+                "Customer not found",
+                "CUSTOMER_NOT_FOUND"
+            ));
+        }
+        Err(e) => {
+            log::error!("Failed to get customer: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch customer",
+                "CUSTOMER_FETCH_ERROR"
+            ));
+        }
+    };
+
+    let result = db.get_customer_quotes(&id).and_then(|quotes| {
+        quotes
+            .into_iter()
+            .map(|quote| {
+                let totals = quote_totals(&quote.id, &db.get_quote_pricing(&quote)?);
+                Ok(CustomerQuoteValue {
+                    quote_id: quote.id,
+                    name: quote.name,
+                    status: quote.status,
+                    net_monthly: totals.net_monthly,
+                    gross_monthly: totals.gross_monthly,
+                })
+            })
+            .collect::<rusqlite::Result<Vec<_>>>()
+    });
+
+    match result {
+        Ok(quotes) => {
+            let open: Vec<&CustomerQuoteValue> = quotes.iter().filter(|q| q.status.is_open()).collect();
+            let open_gross_monthly: f64 = open.iter().map(|q| q.gross_monthly).sum();
+            HttpResponse::Ok().json(CustomerSummary {
+                customer,
+                quote_count: quotes.len() as u32,
+                open_quote_count: open.len() as u32,
+                open_net_monthly: open.iter().map(|q| q.net_monthly).sum(),
+                open_gross_monthly,
+                open_gross_yearly: open_gross_monthly * 12.0,
+                quotes,
+            })
+        }
+        Err(e) => {
+            log::error!("Failed to build customer summary: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to build customer summary",
+                "CUSTOMER_SUMMARY_ERROR"
+            ))
         }
-    } else {
-        HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "No update fields provided"
-        }))
     }
 }
 
-pub async fn delete_quote(
+// Tax profile handlers
+pub async fn get_tax_profiles(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_tax_profiles() {
+        Ok(profiles) => HttpResponse::Ok().json(profiles),
+        Err(e) => {
+            log::error!("Failed to get tax profiles: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch tax profiles",
+                "TAX_PROFILES_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+fn invalid_tax_rate(rate: Option<f64>) -> Option<HttpResponse> {
+    match rate {
+        Some(rate) if !(0.0..=100.0).contains(&rate) => Some(HttpResponse::BadRequest().json(ApiError::new(
+            "Tax rate must be between 0 and 100",
+            "INVALID_TAX_PROFILE"
+        ))),
+        _ => None,
+    }
+}
+
+pub async fn create_tax_profile(
+    data: web::Data<AppState>,
+    body: web::Json<CreateTaxProfile>,
+) -> impl Responder {
+    if body.label.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Tax profile requires a label",
+            "INVALID_TAX_PROFILE"
+        ));
+    }
+    if let Some(resp) = invalid_tax_rate(Some(body.rate)) {
+        return resp;
+    }
+
+    let db = data.db.lock().unwrap();
+    match db.create_tax_profile(&body) {
+        Ok(profile) => HttpResponse::Created().json(profile),
+        Err(e) => {
+            log::error!("Failed to create tax profile: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to create tax profile",
+                "TAX_PROFILE_CREATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn update_tax_profile(
     data: web::Data<AppState>,
     path: web::Path<String>,
+    body: web::Json<UpdateTaxProfile>,
 ) -> impl Responder {
+    if let Some(resp) = invalid_tax_rate(body.rate) {
+        return resp;
+    }
+
     let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    match db.delete_quote(&id) {
-        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
-            "status": "ok"
-        })),
-        Err(e) => {
-            log::error!("Failed to delete quote: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to delete quote"
+    match db.update_tax_profile(&id, &body) {
+        Ok(_) => {
+            data.events.publish(Event::prices_updated("tax_profile"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
             }))
         }
+        Err(e) => {
+            log::error!("Failed to update tax profile: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update tax profile",
+                "TAX_PROFILE_UPDATE_ERROR"
+            ))
+        }
     }
 }
 
-// Quote item handlers
-pub async fn get_items(
+pub async fn delete_tax_profile(
     data: web::Data<AppState>,
     path: web::Path<String>,
 ) -> impl Responder {
-    let quote_id = path.into_inner();
+    let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    match db.get_items(&quote_id) {
-        Ok(items) => HttpResponse::Ok().json(items),
-        Err(e) => {
-            log::error!("Failed to get items: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to fetch items"
+    match db.delete_tax_profile(&id) {
+        Ok(_) => {
+            data.events.publish(Event::prices_updated("tax_profile"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
             }))
         }
+        Err(e) => {
+            log::error!("Failed to delete tax profile: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete tax profile",
+                "TAX_PROFILE_DELETE_ERROR"
+            ))
+        }
     }
 }
 
-pub async fn create_item(
+// Backup policy handlers
+pub async fn get_backup_policies(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_backup_policies() {
+        Ok(policies) => HttpResponse::Ok().json(policies),
+        Err(e) => {
+            log::error!("Failed to get backup policies: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch backup policies",
+                "BACKUP_POLICIES_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn create_backup_policy(
     data: web::Data<AppState>,
-    path: web::Path<String>,
-    body: web::Json<CreateQuoteItem>,
+    body: web::Json<CreateBackupPolicy>,
 ) -> impl Responder {
-    let quote_id = path.into_inner();
-    let db = data.db.lock().unwrap();
+    if body.name.trim().is_empty()
+        || body.retention_days < 1
+        || body.change_rate_pct < 0.0
+        || body.price_per_gb < 0.0
+    {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Backup policy requires a name, at least 1 retention day and non-negative change rate and price",
+            "INVALID_BACKUP_POLICY"
+        ));
+    }
 
-    match db.create_item(&quote_id, &body) {
-        Ok(item) => HttpResponse::Created().json(item),
+    let db = data.db.lock().unwrap();
+    match db.create_backup_policy(&body) {
+        Ok(policy) => HttpResponse::Created().json(policy),
         Err(e) => {
-            log::error!("Failed to create item: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to create item"
-            }))
+            log::error!("Failed to create backup policy: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to create backup policy",
+                "BACKUP_POLICY_CREATE_ERROR"
+            ))
         }
     }
 }
 
-pub async fn update_item(
+pub async fn update_backup_policy(
     data: web::Data<AppState>,
-    path: web::Path<(String, String)>,
-    body: web::Json<UpdateQuoteItem>,
+    path: web::Path<String>,
+    body: web::Json<UpdateBackupPolicy>,
 ) -> impl Responder {
-    let (_, item_id) = path.into_inner();
+    let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    match db.update_item(&item_id, &body) {
+    match db.update_backup_policy(&id, &body) {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({
             "status": "ok"
         })),
         Err(e) => {
-            log::error!("Failed to update item: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to update item"
-            }))
+            log::error!("Failed to update backup policy: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update backup policy",
+                "BACKUP_POLICY_UPDATE_ERROR"
+            ))
         }
     }
 }
 
-pub async fn delete_item(
+pub async fn delete_backup_policy(
     data: web::Data<AppState>,
-    path: web::Path<(String, String)>,
+    path: web::Path<String>,
 ) -> impl Responder {
-    let (_, item_id) = path.into_inner();
+    let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    match db.delete_item(&item_id) {
+    match db.delete_backup_policy(&id) {
         Ok(_) => HttpResponse::Ok().json(serde_json::json!({
             "status": "ok"
         })),
         Err(e) => {
-            log::error!("Failed to delete item: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to delete item"
-            }))
+            log::error!("Failed to delete backup policy: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete backup policy",
+                "BACKUP_POLICY_DELETE_ERROR"
+            ))
         }
     }
 }
 
-// Best match handler - find ECS instances by CPU/RAM requirements
-pub async fn best_match(
+// Quote template handlers
+pub async fn get_templates(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_templates() {
+        Ok(templates) => HttpResponse::Ok().json(templates),
+        Err(e) => {
+            log::error!("Failed to get templates: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch templates",
+                "TEMPLATES_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn get_template(
     data: web::Data<AppState>,
-    query: web::Query<BestMatchQuery>,
+    path: web::Path<String>,
 ) -> impl Responder {
+    let id = path.into_inner();
     let db = data.db.lock().unwrap();
 
-    match db.find_best_match(query.vcpus, query.ram_gb) {
-        Ok(flavors) => HttpResponse::Ok().json(flavors),
+    match db.get_template(&id) {
+        Ok(Some(template)) => HttpResponse::Ok().json(template),
+        Ok(None) => HttpResponse::NotFound().json(ApiError::new(
+            "Template not found",
+            "TEMPLATE_NOT_FOUND"
+        )),
         Err(e) => {
-            log::error!("Failed to find best match: {}", e);
-            HttpResponse::InternalServerError().json(serde_json::json!({
-                "error": "Failed to find matching instances"
-            }))
+            log::error!("Failed to get template: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch template",
+                "TEMPLATE_FETCH_ERROR"
+            ))
         }
     }
 }
+
+// Reject item definitions with bad counts or sizes, or unknown catalog ids
+fn invalid_template_items(db: &Database, items: &[TemplateItem]) -> Option<HttpResponse> {
+    for item in items {
+        if item.count.is_some_and(|n| !(1..=MAX_TEMPLATE_ITEM_COUNT).contains(&n)) {
+            return Some(HttpResponse::BadRequest().json(ApiError::new(
+                &format!("Template item count must be between 1 and {}", MAX_TEMPLATE_ITEM_COUNT),
+                "INVALID_TEMPLATE"
+            )));
+        }
+        if item.disk_type_id.is_some() && item.disk_size_gb.is_none_or(|gb| gb <= 0) {
+            return Some(HttpResponse::BadRequest().json(ApiError::new(
+                "Template items with a disk type need a positive disk_size_gb",
+                "INVALID_TEMPLATE"
+            )));
+        }
+        if let Err(resp) = resolve_template_item(db, item) {
+            return Some(*resp);
+        }
+    }
+    None
+}
+
+pub async fn create_template(
+    data: web::Data<AppState>,
+    body: web::Json<CreateTemplate>,
+) -> impl Responder {
+    if body.name.trim().is_empty() || body.items.is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Template requires a name and at least one item",
+            "INVALID_TEMPLATE"
+        ));
+    }
+
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = invalid_template_items(&db, &body.items) {
+        return resp;
+    }
+
+    match db.create_template(&body) {
+        Ok(template) => HttpResponse::Created().json(template),
+        Err(e) => {
+            log::error!("Failed to create template: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to create template",
+                "TEMPLATE_CREATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn update_template(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<UpdateTemplate>,
+) -> impl Responder {
+    if body.items.as_ref().is_some_and(|items| items.is_empty()) {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Template requires at least one item",
+            "INVALID_TEMPLATE"
+        ));
+    }
+
+    let id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = body.items.as_ref().and_then(|items| invalid_template_items(&db, items)) {
+        return resp;
+    }
+
+    match db.update_template(&id, &body) {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "ok"
+        })),
+        Err(e) => {
+            log::error!("Failed to update template: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update template",
+                "TEMPLATE_UPDATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn delete_template(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    match db.delete_template(&id) {
+        Ok(_) => HttpResponse::Ok().json(serde_json::json!({
+            "status": "ok"
+        })),
+        Err(e) => {
+            log::error!("Failed to delete template: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete template",
+                "TEMPLATE_DELETE_ERROR"
+            ))
+        }
+    }
+}
+
+// Product (SKU) catalog handlers
+pub async fn get_products(
+    data: web::Data<AppState>,
+    query: web::Query<ProductQuery>,
+) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_products(query.category.as_deref()) {
+        Ok(products) => HttpResponse::Ok().json(products),
+        Err(e) => {
+            log::error!("Failed to get products: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch products",
+                "PRODUCTS_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn create_product(
+    data: web::Data<AppState>,
+    body: web::Json<CreateProduct>,
+) -> impl Responder {
+    if body.category.trim().is_empty() || body.name.trim().is_empty() || body.price < 0.0 {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Product requires a category, a name and a non-negative price",
+            "INVALID_PRODUCT"
+        ));
+    }
+
+    let db = data.db.lock().unwrap();
+    match db.create_product(&body) {
+        Ok(product) => {
+            data.events.publish(Event::prices_updated("catalog"));
+            HttpResponse::Created().json(product)
+        }
+        Err(e) => {
+            log::error!("Failed to create product: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to create product",
+                "PRODUCT_CREATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn update_product(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<UpdateProduct>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    match db.update_product(&id, &body) {
+        Ok(true) => {
+            data.events.publish(Event::prices_updated("catalog"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Ok(false) => HttpResponse::NotFound().json(ApiError::new(
+            "Product not found",
+            "PRODUCT_NOT_FOUND"
+        )),
+        Err(e) => {
+            log::error!("Failed to update product: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update product",
+                "PRODUCT_UPDATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn delete_product(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    match db.delete_product(&id) {
+        Ok(true) => {
+            data.events.publish(Event::prices_updated("catalog"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Ok(false) => HttpResponse::NotFound().json(ApiError::new(
+            "Product not found",
+            "PRODUCT_NOT_FOUND"
+        )),
+        Err(e) => {
+            log::error!("Failed to delete product: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete product",
+                "PRODUCT_DELETE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn trigger_crawl(data: web::Data<AppState>) -> impl Responder {
+    let Some(_guard) = CrawlGuard::acquire(&data.crawl_running) else {
+        return HttpResponse::Ok().json(serde_json::json!({
+            "status": "warning",
+            "message": "Crawl already in progress"
+        }));
+    };
+
+    // Trigger the crawler service and wait for the crawl to finish
+    match run_crawl(&data.config, || data.events.publish(Event::crawl_started(false))).await {
+        CrawlOutcome::Completed => {
+            data.events.publish(Event::crawl_completed(true, "Crawl completed", false));
+            data.events.publish(Event::prices_updated("crawl"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok",
+                "message": "Crawl completed"
+            }))
+        }
+        CrawlOutcome::Busy => HttpResponse::Ok().json(serde_json::json!({
+            "status": "warning",
+            "message": "Crawl already in progress"
+        })),
+        CrawlOutcome::Failed(message) => {
+            data.events.publish(Event::crawl_completed(false, message, false));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "warning",
+                "message": message
+            }))
+        }
+    }
+}
+
+// Quote handlers
+pub async fn get_quotes(
+    data: web::Data<AppState>,
+    query: web::Query<PaginationQuery>,
+) -> impl Responder {
+    let db = data.db.lock().unwrap();
+
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(100).min(100);
+    let sort_by = query.sort_by.as_deref().unwrap_or("updated_at");
+    let sort_order = query.sort_order.as_deref().unwrap_or("desc");
+    let search = query.search.as_deref();
+    let customer_id = query.customer_id.as_deref();
+
+    let result = db
+        .get_quotes_paginated(page, limit, sort_by, sort_order, search, customer_id)
+        .and_then(|(quotes, total)| Ok((db.with_comment_counts(quotes)?, total)));
+
+    match result {
+        Ok((quotes, total)) => {
+            let total_pages = ((total as f64) / (limit as f64)).ceil() as u32;
+            HttpResponse::Ok().json(PaginatedResponse {
+                data: quotes,
+                total,
+                page,
+                limit,
+                total_pages,
+            })
+        }
+        Err(e) => {
+            log::error!("Failed to get quotes: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch quotes",
+                "QUOTES_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+// Legacy endpoint for backward compatibility (returns all quotes)
+pub async fn get_quotes_all(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_quotes().and_then(|quotes| db.with_comment_counts(quotes)) {
+        Ok(quotes) => HttpResponse::Ok().json(quotes),
+        Err(e) => {
+            log::error!("Failed to get quotes: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch quotes",
+                "QUOTES_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn get_quote(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    match db.get_quote(&id) {
+        Ok(Some(quote)) => HttpResponse::Ok()
+            .insert_header((header::ETAG, etag(quote.version)))
+            .json(quote),
+        Ok(None) => HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Quote not found"
+        })),
+        Err(e) => {
+            log::error!("Failed to get quote: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch quote"
+            }))
+        }
+    }
+}
+
+// Quote-level adjustment for the customer's default discount, if they have one
+fn customer_discount(customer: Option<&Customer>) -> Option<CreateAdjustment> {
+    let pct = customer?.default_discount_pct.filter(|pct| *pct > 0.0)?;
+    Some(CreateAdjustment {
+        item_id: None,
+        kind: AdjustmentKind::Discount,
+        amount_type: AdjustmentType::Percent,
+        value: pct,
+        category: None,
+        description: Some(CUSTOMER_DISCOUNT.to_string()),
+    })
+}
+
+pub async fn create_quote(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<CreateQuote>,
+) -> impl Responder {
+    let actor = audit_actor(&req);
+    let mut body = body.into_inner();
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = tax_profile_missing(&db, body.tax_profile_id.as_deref()) {
+        return resp;
+    }
+    // Empty id means no tax profile on create
+    body.tax_profile_id = body.tax_profile_id.filter(|id| !id.is_empty());
+
+    let customer = match body.customer_id.as_deref().map(|id| lookup_customer(&db, id)).transpose() {
+        Ok(customer) => customer,
+        Err(resp) => return *resp,
+    };
+
+    // New quotes start with the customer's default discount
+    let result = db.transaction(|| {
+        let quote = db.create_quote(&body)?;
+        if let Some(discount) = customer_discount(customer.as_ref()) {
+            let adjustment = db.create_adjustment(&quote.id, &discount)?;
+            record_change(&db, AuditChange::adjustment(&actor, None, Some(&adjustment)))?;
+        }
+        if let Some(change) = AuditChange::quote(&actor, None, Some(&quote)) {
+            db.record_audit(&change)?;
+        }
+        Ok(quote)
+    });
+
+    match result {
+        Ok(quote) => HttpResponse::Created().json(quote),
+        Err(e) => {
+            log::error!("Failed to create quote: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create quote"
+            }))
+        }
+    }
+}
+
+pub async fn update_quote(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<UpdateQuote>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = tax_profile_missing(&db, body.tax_profile_id.as_deref()) {
+        return resp;
+    }
+
+    // Empty id unassigns the customer
+    let customer_id = body.customer_id.as_deref().filter(|id| !id.is_empty());
+    let customer = match customer_id.map(|id| lookup_customer(&db, id)).transpose() {
+        Ok(customer) => customer,
+        Err(resp) => return *resp,
+    };
+
+    let expected = match if_match_version(&req) {
+        Ok(expected) => expected,
+        Err(resp) => return *resp,
+    };
+    if let Some(expected) = expected {
+        let quote = match lookup_quote(&db, &id) {
+            Ok(quote) => quote,
+            Err(resp) => return *resp,
+        };
+        if quote.version != expected {
+            return version_conflict("Quote", quote.version, &quote);
+        }
+    }
+
+    if body.name.is_some() || body.customer_id.is_some() || body.status.is_some() || body.tax_profile_id.is_some() {
+        let result = db.transaction(|| {
+            let before = db.get_quote(&id)?;
+            db.update_quote(&id, &body)?;
+            let after = db.get_quote(&id)?;
+            // A new customer brings their default discount in place of the old one's
+            let customer_changed = matches!((&before, &after), (Some(b), Some(a)) if b.customer_id != a.customer_id);
+            if customer_changed {
+                let previous = db.get_adjustments(&id)?.into_iter().filter(|a| {
+                    a.item_id.is_none() && a.description.as_deref() == Some(CUSTOMER_DISCOUNT)
+                });
+                for adjustment in previous {
+                    db.delete_adjustment(&id, &adjustment.id)?;
+                    record_change(&db, AuditChange::adjustment(&actor, Some(&adjustment), None))?;
+                }
+                if let Some(discount) = customer_discount(customer.as_ref()) {
+                    let adjustment = db.create_adjustment(&id, &discount)?;
+                    record_change(&db, AuditChange::adjustment(&actor, None, Some(&adjustment)))?;
+                }
+            }
+            if let Some(change) = AuditChange::quote(&actor, before.as_ref(), after.as_ref()) {
+                db.record_audit(&change)?;
+            }
+            Ok(after)
+        });
+        match result {
+            Ok(after) => {
+                if let Some(quote) = &after {
+                    data.events.publish(Event::quote_updated(quote));
+                }
+                ok_with_version(after.map(|quote| quote.version))
+            }
+            Err(e) => {
+                log::error!("Failed to update quote: {}", e);
+                HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Failed to update quote"
+                }))
+            }
+        }
+    } else {
+        HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "No update fields provided"
+        }))
+    }
+}
+
+pub async fn delete_quote(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    let result = db.transaction(|| {
+        let before = db.get_quote(&id)?;
+        db.delete_quote(&id)?;
+        if let Some(change) = AuditChange::quote(&actor, before.as_ref(), None) {
+            db.record_audit(&change)?;
+        }
+        Ok(before.is_some())
+    });
+
+    match result {
+        Ok(deleted) => {
+            if deleted {
+                data.events.publish(Event::quote_deleted(&id));
+            }
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to delete quote: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete quote"
+            }))
+        }
+    }
+}
+
+// Deleted quotes and items awaiting restore or purge
+pub async fn get_trash(data: web::Data<AppState>) -> impl Responder {
+    let db = data.db.lock().unwrap();
+    match db.get_trash() {
+        Ok(trash) => HttpResponse::Ok().json(trash),
+        Err(e) => {
+            log::error!("Failed to get trash: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch trash",
+                "TRASH_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn restore_quote(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    let result = db.transaction(|| {
+        let quote = db.restore_quote(&id)?;
+        if let Some(change) = AuditChange::quote(&actor, None, quote.as_ref()) {
+            db.record_audit(&AuditChange { action: AuditAction::Restore, ..change })?;
+        }
+        Ok(quote)
+    });
+
+    match result {
+        Ok(Some(quote)) => {
+            data.events.publish(Event::quote_updated(&quote));
+            HttpResponse::Ok().json(quote)
+        }
+        Ok(None) => HttpResponse::NotFound().json(ApiError::new(
+            "Quote not found in trash",
+            "QUOTE_NOT_IN_TRASH"
+        )),
+        Err(e) => {
+            log::error!("Failed to restore quote: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to restore quote",
+                "QUOTE_RESTORE_ERROR"
+            ))
+        }
+    }
+}
+
+// Cost projection combining each item's billing mode
+pub async fn get_quote_totals(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    let quote = match lookup_quote(&db, &quote_id) {
+        Ok(quote) => quote,
+        Err(resp) => return *resp,
+    };
+
+    match db.get_quote_pricing(&quote) {
+        Ok(pricing) => HttpResponse::Ok().json(quote_totals(&quote_id, &pricing)),
+        Err(e) => {
+            log::error!("Failed to get items: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch items",
+                "ITEMS_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+// Multi-year TCO - cumulative cost per billing strategy and break-even months
+pub async fn get_quote_tco(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<TcoQuery>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let years = query.years.unwrap_or(3).clamp(1, 10);
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    let result = db.get_items(&quote_id).and_then(|items| {
+        let catalog = db
+            .get_flavors()?
+            .into_iter()
+            .map(|f| (f.id.clone(), f))
+            .collect();
+        Ok(tco_report(&quote_id, &items, &catalog, years))
+    });
+
+    match result {
+        Ok(report) => HttpResponse::Ok().json(report),
+        Err(e) => {
+            log::error!("Failed to build TCO report: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to build TCO report",
+                "TCO_ERROR"
+            ))
+        }
+    }
+}
+
+// CSV export priced with per-item billing modes and usage hours
+pub async fn export_quote_csv(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    let quote = match lookup_quote(&db, &quote_id) {
+        Ok(quote) => quote,
+        Err(resp) => return *resp,
+    };
+
+    match db.get_quote_pricing(&quote) {
+        Ok(pricing) => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", csv_filename(&quote)),
+            ))
+            .body(quote_csv(&quote, &pricing)),
+        Err(e) => {
+            log::error!("Failed to get items: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch items",
+                "ITEMS_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+// Change history of a quote and its items, newest first
+pub async fn get_quote_audit(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<AuditQuery>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(50).clamp(1, 100);
+
+    // History is kept after the quote is deleted
+    match db.get_audit_entries(&quote_id, page, limit) {
+        Ok((entries, total)) => {
+            let total_pages = ((total as f64) / (limit as f64)).ceil() as u32;
+            HttpResponse::Ok().json(PaginatedResponse {
+                data: entries,
+                total,
+                page,
+                limit,
+                total_pages,
+            })
+        }
+        Err(e) => {
+            log::error!("Failed to get audit log: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch audit log",
+                "AUDIT_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+// Quote section handlers
+pub async fn get_sections(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    match db.get_sections(&quote_id) {
+        Ok(sections) => HttpResponse::Ok().json(sections),
+        Err(e) => {
+            log::error!("Failed to get sections: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch sections",
+                "SECTIONS_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn create_section(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CreateSection>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let actor = audit_actor(&req);
+    if body.name.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Section requires a name",
+            "INVALID_SECTION"
+        ));
+    }
+
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    let result = db.transaction(|| {
+        let section = db.create_section(&quote_id, &body)?;
+        record_change(&db, AuditChange::section(&actor, None, Some(&section)))?;
+        Ok(section)
+    });
+
+    match result {
+        Ok(section) => {
+            data.events.publish(Event::totals_changed(&quote_id, "section"));
+            HttpResponse::Created().json(section)
+        }
+        Err(e) => {
+            log::error!("Failed to create section: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to create section",
+                "SECTION_CREATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn update_section(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Json<UpdateSection>,
+) -> impl Responder {
+    let (quote_id, section_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    let before = match lookup_section(&db, &quote_id, &section_id) {
+        Ok(section) => section,
+        Err(resp) => return *resp,
+    };
+
+    let result = db.transaction(|| {
+        db.update_section(&section_id, &body)?;
+        let after = db.get_section(&section_id)?;
+        record_change(&db, AuditChange::section(&actor, Some(&before), after.as_ref()))
+    });
+
+    match result {
+        Ok(_) => {
+            data.events.publish(Event::totals_changed(&quote_id, "section"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to update section: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update section",
+                "SECTION_UPDATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn delete_section(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (quote_id, section_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    let before = match lookup_section(&db, &quote_id, &section_id) {
+        Ok(section) => section,
+        Err(resp) => return *resp,
+    };
+
+    // Items of the section are kept, unsectioned
+    let result = db.transaction(|| {
+        let items = db.get_items(&quote_id)?;
+        db.delete_section(&section_id)?;
+        let mut moved = Vec::new();
+        for item in items.iter().filter(|item| item.section_id.as_deref() == Some(section_id.as_str())) {
+            moved.push((item, db.get_item(&item.id)?));
+        }
+        let changes: Vec<_> = moved.iter().map(|(before, after)| (Some(*before), after.as_ref())).collect();
+        record_item_changes(&db, &actor, &changes)?;
+        record_change(&db, AuditChange::section(&actor, Some(&before), None))
+    });
+
+    match result {
+        Ok(_) => {
+            data.events.publish(Event::totals_changed(&quote_id, "section"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to delete section: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete section",
+                "SECTION_DELETE_ERROR"
+            ))
+        }
+    }
+}
+
+// Comment handlers
+pub async fn get_comments(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    query: web::Query<CommentQuery>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    match db.get_comments(&quote_id, &query) {
+        Ok(comments) => HttpResponse::Ok().json(comments),
+        Err(e) => {
+            log::error!("Failed to get comments: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch comments",
+                "COMMENTS_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn create_comment(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CreateComment>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    if body.body.trim().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Comment requires a body",
+            "INVALID_COMMENT"
+        ));
+    }
+
+    let author = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+    if let Some(resp) = invalid_item(&db, &quote_id, body.item_id.as_deref()) {
+        return resp;
+    }
+
+    match db.create_comment(&quote_id, &author, &body) {
+        Ok(comment) => {
+            data.events.publish(Event::comment_created(&comment));
+            HttpResponse::Created().json(comment)
+        }
+        Err(e) => {
+            log::error!("Failed to create comment: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to create comment",
+                "COMMENT_CREATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn update_comment(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Json<UpdateComment>,
+) -> impl Responder {
+    let (quote_id, comment_id) = path.into_inner();
+    if body.body.as_deref().is_some_and(|text| text.trim().is_empty()) {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Comment body cannot be empty",
+            "INVALID_COMMENT"
+        ));
+    }
+
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    if let Err(resp) = lookup_comment(&db, &quote_id, &comment_id) {
+        return *resp;
+    }
+
+    match db.update_comment(&comment_id, &actor, &body).and_then(|_| db.get_comment(&comment_id)) {
+        Ok(comment) => {
+            if let Some(comment) = &comment {
+                data.events.publish(Event::comment_updated(comment));
+            }
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to update comment: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update comment",
+                "COMMENT_UPDATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn delete_comment(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (quote_id, comment_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    let comment = match lookup_comment(&db, &quote_id, &comment_id) {
+        Ok(comment) => comment,
+        Err(resp) => return *resp,
+    };
+
+    // The comment row is gone afterwards, so the audit log keeps who removed it
+    let result = db.transaction(|| {
+        db.delete_comment(&comment_id)?;
+        record_change(&db, AuditChange::comment(&actor, Some(&comment), None))
+    });
+
+    match result {
+        Ok(_) => {
+            data.events.publish(Event::comment_deleted(&quote_id, &comment_id));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to delete comment: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete comment",
+                "COMMENT_DELETE_ERROR"
+            ))
+        }
+    }
+}
+
+// Reorder sections; the body must list every section of the quote exactly once
+pub async fn reorder_sections(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<SectionOrder>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    let sections = match db.get_sections(&quote_id) {
+        Ok(sections) => sections,
+        Err(e) => {
+            log::error!("Failed to get sections: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch sections",
+                "SECTIONS_FETCH_ERROR"
+            ));
+        }
+    };
+
+    let mut requested: Vec<&String> = body.section_ids.iter().collect();
+    let mut current: Vec<&String> = sections.iter().map(|s| &s.id).collect();
+    requested.sort();
+    current.sort();
+    if requested != current {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "section_ids must list every section of the quote exactly once",
+            "INVALID_SECTION_ORDER"
+        ));
+    }
+
+    let result = db.transaction(|| {
+        db.reorder_sections(&quote_id, &body.section_ids)?;
+        for before in &sections {
+            let after = db.get_section(&before.id)?;
+            if after.as_ref().is_some_and(|after| after.position != before.position) {
+                record_change(&db, AuditChange::section(&actor, Some(before), after.as_ref()))?;
+            }
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(_) => {
+            data.events.publish(Event::totals_changed(&quote_id, "section"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to reorder sections: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to reorder sections",
+                "SECTION_ORDER_ERROR"
+            ))
+        }
+    }
+}
+
+// Price adjustment handlers
+pub async fn get_adjustments(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    match db.get_adjustments(&quote_id) {
+        Ok(adjustments) => HttpResponse::Ok().json(adjustments),
+        Err(e) => {
+            log::error!("Failed to get adjustments: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch adjustments",
+                "ADJUSTMENTS_FETCH_ERROR"
+            ))
+        }
+    }
+}
+
+// Discounts are capped at 100%; values are never negative
+fn invalid_adjustment(kind: AdjustmentKind, amount_type: AdjustmentType, value: f64) -> Option<HttpResponse> {
+    let over_discount = kind == AdjustmentKind::Discount && amount_type == AdjustmentType::Percent && value > 100.0;
+    if value < 0.0 || !value.is_finite() || over_discount {
+        return Some(HttpResponse::BadRequest().json(ApiError::new(
+            "Adjustment value must be non-negative, and percentage discounts at most 100",
+            "INVALID_ADJUSTMENT"
+        )));
+    }
+    None
+}
+
+pub async fn create_adjustment(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CreateAdjustment>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let actor = audit_actor(&req);
+
+    if let Some(resp) = invalid_adjustment(body.kind, body.amount_type, body.value) {
+        return resp;
+    }
+
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    // Item-level adjustments must target an item on this quote
+    if let Some(item_id) = &body.item_id {
+        match db.get_item(item_id) {
+            Ok(Some(item)) if item.quote_id == quote_id => {}
+            Ok(_) => {
+                return HttpResponse::BadRequest().json(ApiError::with_details(
+                    "Item not found on this quote",
+                    "INVALID_ADJUSTMENT_ITEM",
+                    item_id
+                ));
+            }
+            Err(e) => {
+                log::error!("Failed to get item: {}", e);
+                return HttpResponse::InternalServerError().json(ApiError::new(
+                    "Failed to fetch item",
+                    "ITEM_FETCH_ERROR"
+                ));
+            }
+        }
+    }
+
+    let result = db.transaction(|| {
+        let adjustment = db.create_adjustment(&quote_id, &body)?;
+        record_change(&db, AuditChange::adjustment(&actor, None, Some(&adjustment)))?;
+        Ok(adjustment)
+    });
+
+    match result {
+        Ok(adjustment) => {
+            data.events.publish(Event::totals_changed(&quote_id, "adjustment"));
+            HttpResponse::Created().json(adjustment)
+        }
+        Err(e) => {
+            log::error!("Failed to create adjustment: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to create adjustment",
+                "ADJUSTMENT_CREATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn update_adjustment(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Json<UpdateAdjustment>,
+) -> impl Responder {
+    let (quote_id, adjustment_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    let current = match db.get_adjustments(&quote_id) {
+        Ok(adjustments) => adjustments.into_iter().find(|a| a.id == adjustment_id),
+        Err(e) => {
+            log::error!("Failed to get adjustments: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch adjustments",
+                "ADJUSTMENTS_FETCH_ERROR"
+            ));
+        }
+    };
+    let Some(current) = current else {
+        return HttpResponse::NotFound().json(ApiError::new(
+            "Adjustment not found",
+            "ADJUSTMENT_NOT_FOUND"
+        ));
+    };
+
+    if let Some(resp) = invalid_adjustment(
+        body.kind.unwrap_or(current.kind),
+        body.amount_type.unwrap_or(current.amount_type),
+        body.value.unwrap_or(current.value),
+    ) {
+        return resp;
+    }
+
+    let result = db.transaction(|| {
+        db.update_adjustment(&adjustment_id, &body)?;
+        let after = db.get_adjustment(&adjustment_id)?;
+        record_change(&db, AuditChange::adjustment(&actor, Some(&current), after.as_ref()))
+    });
+
+    match result {
+        Ok(_) => {
+            data.events.publish(Event::totals_changed(&quote_id, "adjustment"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to update adjustment: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to update adjustment",
+                "ADJUSTMENT_UPDATE_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn delete_adjustment(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (quote_id, adjustment_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    let result = db.transaction(|| {
+        let before = db.get_adjustment(&adjustment_id)?.filter(|a| a.quote_id == quote_id);
+        if before.is_some() {
+            db.delete_adjustment(&quote_id, &adjustment_id)?;
+            record_change(&db, AuditChange::adjustment(&actor, before.as_ref(), None))?;
+        }
+        Ok(before.is_some())
+    });
+
+    match result {
+        Ok(true) => {
+            data.events.publish(Event::totals_changed(&quote_id, "adjustment"));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Ok(false) => HttpResponse::NotFound().json(ApiError::new(
+            "Adjustment not found",
+            "ADJUSTMENT_NOT_FOUND"
+        )),
+        Err(e) => {
+            log::error!("Failed to delete adjustment: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to delete adjustment",
+                "ADJUSTMENT_DELETE_ERROR"
+            ))
+        }
+    }
+}
+
+// Quote item handlers
+pub async fn get_items(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let db = data.db.lock().unwrap();
+
+    match db.get_items(&quote_id) {
+        Ok(items) => HttpResponse::Ok().json(items),
+        Err(e) => {
+            log::error!("Failed to get items: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to fetch items"
+            }))
+        }
+    }
+}
+
+pub async fn create_item(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<CreateQuoteItem>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let actor = audit_actor(&req);
+    let mut body = body.into_inner();
+    let db = data.db.lock().unwrap();
+
+    // Items added to a trashed quote would stay hidden until it's restored
+    match db.quote_in_trash(&quote_id) {
+        Ok(false) => {}
+        Ok(true) => {
+            return HttpResponse::Conflict().json(ApiError::new(
+                "Restore the quote before adding items",
+                "QUOTE_IN_TRASH"
+            ));
+        }
+        Err(e) => {
+            log::error!("Failed to get quote: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch quote",
+                "QUOTE_FETCH_ERROR"
+            ));
+        }
+    }
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    if let Some(resp) = invalid_section(&db, &quote_id, body.section_id.as_deref()) {
+        return resp;
+    }
+    // Empty id means no section on create
+    body.section_id = body.section_id.filter(|id| !id.is_empty());
+
+    // SKU details come from the catalog, never from the client
+    body.sku = SkuDetails::default();
+    if let Some(sku_id) = body.sku_id.clone() {
+        match lookup_product(&db, &sku_id) {
+            Ok(product) => body.sku = SkuDetails::from(&product),
+            Err(resp) => return *resp,
+        }
+    }
+
+    // Bandwidth is priced from the catalog, never taken from the client
+    body.bandwidth_price = None;
+    if let Some(bandwidth_id) = body.bandwidth_type_id.clone() {
+        match lookup_bandwidth(&db, &bandwidth_id) {
+            Ok(bandwidth) => {
+                body.bandwidth_type_name = Some(bandwidth.name.clone());
+                body.bandwidth_price = Some(bandwidth.monthly_price(
+                    body.eip_count,
+                    body.bandwidth_mbps,
+                    body.traffic_gb,
+                ));
+            }
+            Err(resp) => return *resp,
+        }
+    }
+
+    // Licence details come from the image catalog, never from the client
+    body.os_image_name = None;
+    body.os_licence_price = None;
+    if let Some(image_id) = body.os_image.clone() {
+        match lookup_image(&db, &image_id) {
+            Ok(image) => {
+                body.os_image_name = Some(image.name.clone());
+                body.os_licence_price = Some(image.price_per_vcpu_hour);
+            }
+            Err(resp) => return *resp,
+        }
+    }
+
+    // Backup settings come from the policy, never from the client
+    body.backup = BackupDetails::default();
+    if let Some(policy_id) = body.backup_policy_id.clone() {
+        match lookup_backup_policy(&db, &policy_id) {
+            Ok(policy) => body.backup = BackupDetails::from(&policy),
+            Err(resp) => return *resp,
+        }
+    }
+
+    let result = db.transaction(|| {
+        let item = db.create_item(&quote_id, &body)?;
+        record_item_change(&db, &actor, None, Some(&item))?;
+        Ok(item)
+    });
+
+    match result {
+        Ok(item) => {
+            data.events.publish(Event::item_created(&item));
+            HttpResponse::Created().json(item)
+        }
+        Err(e) => {
+            log::error!("Failed to create item: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to create item"
+            }))
+        }
+    }
+}
+
+pub async fn update_item(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+    body: web::Json<UpdateQuoteItem>,
+) -> impl Responder {
+    let (quote_id, item_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let mut body = body.into_inner();
+    let db = data.db.lock().unwrap();
+
+    let expected = match if_match_version(&req) {
+        Ok(expected) => expected,
+        Err(resp) => return *resp,
+    };
+    let current = match lookup_item(&db, &quote_id, &item_id) {
+        Ok(item) => item,
+        Err(resp) => return *resp,
+    };
+    if let Some(expected) = expected {
+        if current.version != expected {
+            return version_conflict("Item", current.version, &current);
+        }
+    }
+
+    if let Some(resp) = invalid_section(&db, &quote_id, body.section_id.as_deref()) {
+        return resp;
+    }
+
+    // SKU details come from the catalog, never from the client
+    body.sku = SkuDetails::default();
+    if let Some(sku_id) = body.sku_id.clone() {
+        match lookup_product(&db, &sku_id) {
+            Ok(product) => body.sku = SkuDetails::from(&product),
+            Err(resp) => return *resp,
+        }
+    }
+
+    // Licence details come from the image catalog, never from the client
+    body.os_image_name = None;
+    body.os_licence_price = None;
+    if let Some(image_id) = body.os_image.clone() {
+        match lookup_image(&db, &image_id) {
+            Ok(image) => {
+                body.os_image_name = Some(image.name.clone());
+                body.os_licence_price = Some(image.price_per_vcpu_hour);
+            }
+            Err(resp) => return *resp,
+        }
+    }
+
+    // Backup settings come from the policy, never from the client
+    body.backup = BackupDetails::default();
+    if let Some(policy_id) = body.backup_policy_id.clone() {
+        match lookup_backup_policy(&db, &policy_id) {
+            Ok(policy) => body.backup = BackupDetails::from(&policy),
+            Err(resp) => return *resp,
+        }
+    }
+
+    // Bandwidth is always priced here from the catalog, never taken from the
+    // client; re-price when any of its inputs change, merged with the stored item
+    body.bandwidth_price = None;
+    let bandwidth_changed = body.bandwidth_type_id.is_some()
+        || body.eip_count.is_some()
+        || body.bandwidth_mbps.is_some()
+        || body.traffic_gb.is_some();
+    if bandwidth_changed {
+        if let Some(bandwidth_id) = body.bandwidth_type_id.clone().or(current.bandwidth_type_id) {
+            match lookup_bandwidth(&db, &bandwidth_id) {
+                Ok(bandwidth) => {
+                    body.bandwidth_type_name = Some(bandwidth.name.clone());
+                    body.bandwidth_price = Some(bandwidth.monthly_price(
+                        body.eip_count.or(current.eip_count),
+                        body.bandwidth_mbps.or(current.bandwidth_mbps),
+                        body.traffic_gb.or(current.traffic_gb),
+                    ));
+                }
+                Err(resp) => return *resp,
+            }
+        }
+    }
+
+    let result = db.transaction(|| {
+        let before = db.get_item(&item_id)?;
+        db.update_item(&item_id, &body)?;
+        let after = db.get_item(&item_id)?;
+        record_item_change(&db, &actor, before.as_ref(), after.as_ref())?;
+        Ok(after)
+    });
+
+    match result {
+        Ok(after) => {
+            if let Some(item) = &after {
+                data.events.publish(Event::item_updated(item));
+            }
+            ok_with_version(after.map(|item| item.version))
+        }
+        Err(e) => {
+            log::error!("Failed to update item: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update item"
+            }))
+        }
+    }
+}
+
+// Reorder items; the body must list every item of the quote exactly once
+pub async fn reorder_items(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ItemOrder>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    let items = match db.get_items(&quote_id) {
+        Ok(items) => items,
+        Err(e) => {
+            log::error!("Failed to get items: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch items",
+                "ITEMS_FETCH_ERROR"
+            ));
+        }
+    };
+
+    let mut requested: Vec<&String> = body.item_ids.iter().collect();
+    let mut current: Vec<&String> = items.iter().map(|i| &i.id).collect();
+    requested.sort();
+    current.sort();
+    if requested != current {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "item_ids must list every item of the quote exactly once",
+            "INVALID_ITEM_ORDER"
+        ));
+    }
+
+    let result = db.transaction(|| {
+        db.reorder_items(&quote_id, &body.item_ids)?;
+        let mut moved = Vec::new();
+        for before in &items {
+            let after = db.get_item(&before.id)?;
+            if after.as_ref().is_some_and(|after| after.position != before.position) {
+                moved.push((before, after));
+            }
+        }
+        let changes: Vec<_> = moved.iter().map(|(before, after)| (Some(*before), after.as_ref())).collect();
+        record_item_changes(&db, &actor, &changes)
+    });
+
+    match result {
+        Ok(_) => {
+            data.events.publish(Event::items_reordered(&quote_id, &body.item_ids));
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to reorder items: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to reorder items",
+                "ITEM_ORDER_ERROR"
+            ))
+        }
+    }
+}
+
+// Instantiate a template into the quote, priced from the current catalog
+pub async fn apply_template(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ApplyTemplate>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+    if let Some(resp) = invalid_section(&db, &quote_id, body.section_id.as_deref()) {
+        return resp;
+    }
+
+    let template = match db.get_template(&body.template_id) {
+        Ok(Some(template)) => template,
+        Ok(None) => {
+            return HttpResponse::BadRequest().json(ApiError::with_details(
+                "Unknown template",
+                "INVALID_TEMPLATE",
+                &body.template_id
+            ))
+        }
+        Err(e) => {
+            log::error!("Failed to get template: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch template",
+                "TEMPLATE_FETCH_ERROR"
+            ));
+        }
+    };
+
+    let section_id = body.section_id.as_deref().filter(|id| !id.is_empty());
+    let mut items = Vec::new();
+    for definition in &template.items {
+        let (flavor, disk) = match resolve_template_item(&db, definition) {
+            Ok(resolved) => resolved,
+            Err(resp) => return *resp,
+        };
+        items.extend(definition.instantiate(flavor.as_ref(), disk.as_ref(), body.billing_mode, section_id));
+    }
+
+    let result = db.transaction(|| {
+        let created = db.create_items(&quote_id, &items)?;
+        let changes: Vec<_> = created.iter().map(|item| (None, Some(item))).collect();
+        record_item_changes(&db, &actor, &changes)?;
+        Ok(created)
+    });
+
+    match result {
+        Ok(created) => {
+            for item in &created {
+                data.events.publish(Event::item_created(item));
+            }
+            HttpResponse::Created().json(created)
+        }
+        Err(e) => {
+            log::error!("Failed to apply template: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to apply template",
+                "TEMPLATE_APPLY_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn delete_item(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (quote_id, item_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    if let Err(resp) = lookup_item(&db, &quote_id, &item_id) {
+        return *resp;
+    }
+
+    let result = db.transaction(|| {
+        let before = db.get_item(&item_id)?;
+        db.delete_item(&item_id)?;
+        record_item_change(&db, &actor, before.as_ref(), None)?;
+        Ok(before)
+    });
+
+    match result {
+        Ok(before) => {
+            if let Some(item) = before {
+                data.events.publish(Event::item_deleted(&item.quote_id, &item.id));
+            }
+            HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            }))
+        }
+        Err(e) => {
+            log::error!("Failed to delete item: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete item"
+            }))
+        }
+    }
+}
+
+pub async fn restore_item(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<(String, String)>,
+) -> impl Responder {
+    let (quote_id, item_id) = path.into_inner();
+    let actor = audit_actor(&req);
+    let db = data.db.lock().unwrap();
+
+    // Items come back with their quote; restoring one alone would leave it hidden
+    match db.quote_in_trash(&quote_id) {
+        Ok(false) => {}
+        Ok(true) => {
+            return HttpResponse::Conflict().json(ApiError::new(
+                "Restore the quote before its items",
+                "QUOTE_IN_TRASH"
+            ));
+        }
+        Err(e) => {
+            log::error!("Failed to get quote: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch quote",
+                "QUOTE_FETCH_ERROR"
+            ));
+        }
+    }
+
+    let result = db.transaction(|| {
+        let item = db.restore_item(&quote_id, &item_id)?;
+        if let Some(change) = AuditChange::item(&actor, None, item.as_ref()) {
+            db.push_journal(&uuid::Uuid::new_v4().to_string(), &quote_id, &item_id, None, item.as_ref())?;
+            db.record_audit(&AuditChange { action: AuditAction::Restore, ..change })?;
+        }
+        Ok(item)
+    });
+
+    match result {
+        Ok(Some(item)) => {
+            data.events.publish(Event::item_created(&item));
+            HttpResponse::Ok().json(item)
+        }
+        Ok(None) => HttpResponse::NotFound().json(ApiError::new(
+            "Item not found in trash",
+            "ITEM_NOT_IN_TRASH"
+        )),
+        Err(e) => {
+            log::error!("Failed to restore item: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to restore item",
+                "ITEM_RESTORE_ERROR"
+            ))
+        }
+    }
+}
+
+enum JournalOutcome {
+    Stepped(JournalStep, Vec<Event>),
+    // Item changed since the journal recorded it
+    Conflict(String),
+}
+
+// Whether an item is still in the state a journal entry left it in. Version and
+// update time are ignored: reorders and every undo and redo bump them.
+fn same_item_state(current: Option<&QuoteItem>, expected: Option<&QuoteItem>) -> bool {
+    match (current, expected) {
+        (None, None) => true,
+        (Some(current), Some(expected)) => {
+            let current = QuoteItem {
+                version: expected.version,
+                updated_at: expected.updated_at.clone(),
+                ..current.clone()
+            };
+            serde_json::json!(current) == serde_json::json!(expected)
+        }
+        _ => false,
+    }
+}
+
+// Undo or redo the quote's next journal group by writing back the item states
+// on the other side of it. Refused when any of its items has changed since.
+fn step_journal(req: &HttpRequest, data: &AppState, quote_id: &str, undo: bool) -> HttpResponse {
+    let actor = audit_actor(req);
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, quote_id) {
+        return resp;
+    }
+
+    let result = db.transaction(|| {
+        let entries = db.next_journal_group(quote_id, undo)?;
+        let Some(group_id) = entries.first().map(|entry| entry.group_id.clone()) else {
+            return Ok(None);
+        };
+
+        // Every item must be checked before any is written back
+        for entry in &entries {
+            let expected = if undo { &entry.after } else { &entry.before };
+            if !same_item_state(db.get_item(&entry.item_id)?.as_ref(), expected.as_ref()) {
+                return Ok(Some(JournalOutcome::Conflict(entry.item_id.clone())));
+            }
+        }
+
+        let mut operations = Vec::new();
+        let mut items = Vec::new();
+        let mut events = Vec::new();
+        for entry in entries {
+            let target = if undo { &entry.before } else { &entry.after };
+            let current = db.get_item(&entry.item_id)?;
+            db.put_item_state(&entry.item_id, target.as_ref())?;
+            db.set_journal_undone(&entry.id, undo)?;
+            let item = db.get_item(&entry.item_id)?;
+            if let Some(change) = AuditChange::item(&actor, current.as_ref(), item.as_ref()) {
+                db.record_audit(&change)?;
+            }
+
+            match (&current, &item) {
+                (None, Some(item)) => events.push(Event::item_created(item)),
+                (Some(_), Some(item)) => events.push(Event::item_updated(item)),
+                (Some(_), None) => events.push(Event::item_deleted(quote_id, &entry.item_id)),
+                (None, None) => {}
+            }
+            items.extend(item);
+            operations.push(JournalEntry { undone: undo, ..entry });
+        }
+
+        let (undo_available, redo_available) = db.journal_counts(quote_id)?;
+        let step = JournalStep {
+            group_id,
+            operations,
+            items,
+            undo_available,
+            redo_available,
+        };
+        Ok(Some(JournalOutcome::Stepped(step, events)))
+    });
+
+    match result {
+        Ok(Some(JournalOutcome::Stepped(step, events))) => {
+            for event in events {
+                data.events.publish(event);
+            }
+            HttpResponse::Ok().json(step)
+        }
+        Ok(Some(JournalOutcome::Conflict(item_id))) => HttpResponse::Conflict().json(ApiError::with_details(
+            "Item has changed since this step was recorded",
+            "JOURNAL_CONFLICT",
+            &item_id
+        )),
+        Ok(None) if undo => HttpResponse::BadRequest().json(ApiError::new(
+            "Nothing to undo",
+            "NOTHING_TO_UNDO"
+        )),
+        Ok(None) => HttpResponse::BadRequest().json(ApiError::new(
+            "Nothing to redo",
+            "NOTHING_TO_REDO"
+        )),
+        Err(e) => {
+            log::error!("Failed to step quote journal: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to apply undo/redo",
+                "JOURNAL_ERROR"
+            ))
+        }
+    }
+}
+
+pub async fn undo_quote(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    step_journal(&req, &data, &path.into_inner(), true)
+}
+
+pub async fn redo_quote(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    step_journal(&req, &data, &path.into_inner(), false)
+}
+
+// Server-sent events for one quote: item changes, quote updates and global events
+pub async fn quote_events(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    if let Some(resp) = quote_missing(&data.db.lock().unwrap(), &quote_id) {
+        return resp;
+    }
+
+    event_stream(data.events.subscribe(Some(quote_id)))
+}
+
+// Server-sent events for crawl completion and price updates
+pub async fn global_events(data: web::Data<AppState>) -> impl Responder {
+    event_stream(data.events.subscribe(None))
+}
+
+fn event_stream(stream: EventStream) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Stops nginx in front of the API from buffering the stream
+        .insert_header(("X-Accel-Buffering", "no"))
+        .body(stream)
+}
+
+// Audit and undo journal entries for an item change; nothing when neither state exists
+fn record_item_change(
+    db: &Database,
+    actor: &str,
+    before: Option<&QuoteItem>,
+    after: Option<&QuoteItem>,
+) -> rusqlite::Result<()> {
+    record_item_changes(db, actor, &[(before, after)])
+}
+
+// Audit item changes made by one request and journal them as a single undo step
+fn record_item_changes(
+    db: &Database,
+    actor: &str,
+    changes: &[(Option<&QuoteItem>, Option<&QuoteItem>)],
+) -> rusqlite::Result<()> {
+    let group_id = uuid::Uuid::new_v4().to_string();
+    for &(before, after) in changes {
+        let Some(change) = AuditChange::item(actor, before, after) else {
+            continue;
+        };
+        db.push_journal(&group_id, &change.quote_id, &change.entity_id, before, after)?;
+        db.record_audit(&change)?;
+    }
+    Ok(())
+}
+
+// Audit a change that isn't journaled (sections, adjustments, comments), if there was one
+fn record_change(db: &Database, change: Option<AuditChange>) -> rusqlite::Result<()> {
+    change.map_or(Ok(()), |change| db.record_audit(&change))
+}
+
+// Who made a change, from the X-Actor header set by the client
+fn audit_actor(req: &HttpRequest) -> String {
+    req.headers()
+        .get("X-Actor")
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|actor| !actor.is_empty())
+        .unwrap_or("anonymous")
+        .to_string()
+}
+
+// Quoted version for the ETag header
+fn etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+// Version required by the If-Match header; None when it's absent or `*`
+fn if_match_version(req: &HttpRequest) -> Result<Option<i64>, Box<HttpResponse>> {
+    let Some(value) = req.headers().get(header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let tag = value.to_str().unwrap_or_default().trim();
+    if tag == "*" {
+        return Ok(None);
+    }
+    tag.trim_start_matches("W/").trim_matches('"').parse().map(Some).map_err(|_| {
+        Box::new(HttpResponse::BadRequest().json(ApiError::with_details(
+            "Invalid If-Match header",
+            "INVALID_IF_MATCH",
+            "Expected the ETag or version of the quote or item"
+        )))
+    })
+}
+
+// 409 with the current state and version, so the client can merge or warn
+fn version_conflict<T: serde::Serialize>(entity: &str, version: i64, current: &T) -> HttpResponse {
+    HttpResponse::Conflict()
+        .insert_header((header::ETAG, etag(version)))
+        .json(serde_json::json!({
+            "error": format!("{} was changed by someone else", entity),
+            "code": "VERSION_CONFLICT",
+            "current": current
+        }))
+}
+
+// {"status":"ok"} with the ETag of the updated version
+fn ok_with_version(version: Option<i64>) -> HttpResponse {
+    let mut resp = HttpResponse::Ok();
+    if let Some(version) = version {
+        resp.insert_header((header::ETAG, etag(version)));
+    }
+    resp.json(serde_json::json!({
+        "status": "ok"
+    }))
+}
+
+// Quote by id, or the error response to return
+fn lookup_quote(db: &Database, quote_id: &str) -> Result<Quote, Box<HttpResponse>> {
+    match db.get_quote(quote_id) {
+        Ok(Some(quote)) => Ok(quote),
+        Ok(None) => Err(Box::new(HttpResponse::NotFound().json(ApiError::new(
+            "Quote not found",
+            "QUOTE_NOT_FOUND"
+        )))),
+        Err(e) => {
+            log::error!("Failed to get quote: {}", e);
+            Err(Box::new(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch quote",
+                "QUOTE_FETCH_ERROR"
+            ))))
+        }
+    }
+}
+
+// Item by id, or the error response to return; items of other quotes aren't found
+fn lookup_item(db: &Database, quote_id: &str, item_id: &str) -> Result<QuoteItem, Box<HttpResponse>> {
+    match db.get_item(item_id) {
+        Ok(Some(item)) if item.quote_id == quote_id => Ok(item),
+        Ok(_) => Err(Box::new(HttpResponse::NotFound().json(ApiError::new(
+            "Item not found",
+            "ITEM_NOT_FOUND"
+        )))),
+        Err(e) => {
+            log::error!("Failed to get item: {}", e);
+            Err(Box::new(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch item",
+                "ITEM_FETCH_ERROR"
+            ))))
+        }
+    }
+}
+
+// Reject sections that don't belong to the quote; an empty id (unassign) is allowed
+fn invalid_section(db: &Database, quote_id: &str, section_id: Option<&str>) -> Option<HttpResponse> {
+    let section_id = section_id.filter(|id| !id.is_empty())?;
+    match db.get_section(section_id) {
+        Ok(Some(section)) if section.quote_id == quote_id => None,
+        Ok(_) => Some(HttpResponse::BadRequest().json(ApiError::with_details(
+            "Section not found on this quote",
+            "INVALID_SECTION",
+            section_id
+        ))),
+        Err(e) => {
+            log::error!("Failed to get section: {}", e);
+            Some(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch section",
+                "SECTION_FETCH_ERROR"
+            )))
+        }
+    }
+}
+
+fn invalid_item(db: &Database, quote_id: &str, item_id: Option<&str>) -> Option<HttpResponse> {
+    let item_id = item_id?;
+    match db.get_item(item_id) {
+        Ok(Some(item)) if item.quote_id == quote_id => None,
+        Ok(_) => Some(HttpResponse::BadRequest().json(ApiError::with_details(
+            "Item not found on this quote",
+            "INVALID_ITEM",
+            item_id
+        ))),
+        Err(e) => {
+            log::error!("Failed to get item: {}", e);
+            Some(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch item",
+                "ITEM_FETCH_ERROR"
+            )))
+        }
+    }
+}
+
+// Section of the given quote, or the error response to return
+fn lookup_section(db: &Database, quote_id: &str, section_id: &str) -> Result<QuoteSection, Box<HttpResponse>> {
+    match db.get_section(section_id) {
+        Ok(Some(section)) if section.quote_id == quote_id => Ok(section),
+        Ok(_) => Err(Box::new(HttpResponse::NotFound().json(ApiError::new(
+            "Section not found",
+            "SECTION_NOT_FOUND"
+        )))),
+        Err(e) => {
+            log::error!("Failed to get section: {}", e);
+            Err(Box::new(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch section",
+                "SECTION_FETCH_ERROR"
+            ))))
+        }
+    }
+}
+
+// Comment on the given quote, or the error response to return
+fn lookup_comment(db: &Database, quote_id: &str, comment_id: &str) -> Result<Comment, Box<HttpResponse>> {
+    match db.get_comment(comment_id) {
+        Ok(Some(comment)) if comment.quote_id == quote_id => Ok(comment),
+        Ok(_) => Err(Box::new(HttpResponse::NotFound().json(ApiError::new(
+            "Comment not found",
+            "COMMENT_NOT_FOUND"
+        )))),
+        Err(e) => {
+            log::error!("Failed to get comment: {}", e);
+            Err(Box::new(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch comment",
+                "COMMENT_FETCH_ERROR"
+            ))))
+        }
+    }
+}
+
+// Entry a request refers to by id, or the error response to return: 400 with
+// `invalid_code` when there is none, 500 with `fetch_code` when the query fails
+fn lookup_reference<T>(
+    found: rusqlite::Result<Option<T>>,
+    id: &str,
+    what: &str,
+    invalid_code: &str,
+    fetch_code: &str,
+) -> Result<T, Box<HttpResponse>> {
+    match found {
+        Ok(Some(entry)) => Ok(entry),
+        Ok(None) => Err(Box::new(HttpResponse::BadRequest().json(ApiError::with_details(
+            &format!("Unknown {}", what),
+            invalid_code,
+            id
+        )))),
+        Err(e) => {
+            log::error!("Failed to get {}: {}", what, e);
+            Err(Box::new(HttpResponse::InternalServerError().json(ApiError::new(
+                &format!("Failed to fetch {}", what),
+                fetch_code
+            ))))
+        }
+    }
+}
+
+// Customer by id, or the error response to return
+fn lookup_customer(db: &Database, customer_id: &str) -> Result<Customer, Box<HttpResponse>> {
+    lookup_reference(db.get_customer(customer_id), customer_id, "customer", "INVALID_CUSTOMER", "CUSTOMER_FETCH_ERROR")
+}
+
+// Reject references to tax profiles that don't exist; an empty id (clear) is allowed
+fn tax_profile_missing(db: &Database, profile_id: Option<&str>) -> Option<HttpResponse> {
+    let profile_id = profile_id.filter(|id| !id.is_empty())?;
+    match db.get_tax_profile(profile_id) {
+        Ok(Some(_)) => None,
+        Ok(None) => Some(HttpResponse::BadRequest().json(ApiError::with_details(
+            "Unknown tax profile",
+            "INVALID_TAX_PROFILE",
+            profile_id
+        ))),
+        Err(e) => {
+            log::error!("Failed to get tax profile: {}", e);
+            Some(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch tax profile",
+                "TAX_PROFILE_FETCH_ERROR"
+            )))
+        }
+    }
+}
+
+// 404/500 response when the quote can't be loaded, None when it exists
+fn quote_missing(db: &Database, quote_id: &str) -> Option<HttpResponse> {
+    match db.get_quote(quote_id) {
+        Ok(Some(_)) => None,
+        Ok(None) => Some(HttpResponse::NotFound().json(ApiError::new(
+            "Quote not found",
+            "QUOTE_NOT_FOUND"
+        ))),
+        Err(e) => {
+            log::error!("Failed to get quote: {}", e);
+            Some(HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to fetch quote",
+                "QUOTE_FETCH_ERROR"
+            )))
+        }
+    }
+}
+
+// Catalog SKU referenced by a quote item, or the error response to return
+fn lookup_product(db: &Database, sku_id: &str) -> Result<Product, Box<HttpResponse>> {
+    lookup_reference(db.get_product(sku_id), sku_id, "product SKU", "INVALID_SKU", "PRODUCT_FETCH_ERROR")
+}
+
+// Bandwidth type referenced by a quote item, or the error response to return
+fn lookup_bandwidth(db: &Database, bandwidth_type_id: &str) -> Result<BandwidthType, Box<HttpResponse>> {
+    lookup_reference(db.get_bandwidth_type(bandwidth_type_id), bandwidth_type_id, "bandwidth type", "INVALID_BANDWIDTH_TYPE", "BANDWIDTH_FETCH_ERROR")
+}
+
+// Backup policy referenced by a quote item, or the error response to return
+fn lookup_backup_policy(db: &Database, policy_id: &str) -> Result<BackupPolicy, Box<HttpResponse>> {
+    lookup_reference(db.get_backup_policy(policy_id), policy_id, "backup policy", "INVALID_BACKUP_POLICY", "BACKUP_POLICY_FETCH_ERROR")
+}
+
+// OS image referenced by a quote item, or the error response to return
+fn lookup_image(db: &Database, image_id: &str) -> Result<Image, Box<HttpResponse>> {
+    lookup_reference(db.get_image(image_id), image_id, "OS image", "INVALID_OS_IMAGE", "IMAGE_FETCH_ERROR")
+}
+
+// Flavor referenced by a template, or the error response to return
+fn lookup_flavor(db: &Database, flavor_id: &str) -> Result<Flavor, Box<HttpResponse>> {
+    lookup_reference(db.get_flavor(flavor_id), flavor_id, "flavor", "INVALID_FLAVOR", "FLAVOR_FETCH_ERROR")
+}
+
+// Disk type referenced by a template, or the error response to return
+fn lookup_disk_type(db: &Database, disk_type_id: &str) -> Result<DiskType, Box<HttpResponse>> {
+    lookup_reference(db.get_disk_type(disk_type_id), disk_type_id, "disk type", "INVALID_DISK_TYPE", "DISK_TYPE_FETCH_ERROR")
+}
+
+// Catalog entries for a template item definition
+type ResolvedTemplateItem = (Option<Flavor>, Option<DiskType>);
+
+fn resolve_template_item(db: &Database, item: &TemplateItem) -> Result<ResolvedTemplateItem, Box<HttpResponse>> {
+    let flavor = item.flavor_id.as_deref().map(|id| lookup_flavor(db, id)).transpose()?;
+    let disk = item.disk_type_id.as_deref().map(|id| lookup_disk_type(db, id)).transpose()?;
+    Ok((flavor, disk))
+}
+
+// Reject unknown flavor families before they reach the matcher
+fn invalid_family(family: Option<&str>) -> Option<HttpResponse> {
+    match family {
+        Some(f) if !FLAVOR_FAMILIES.contains(&f) => Some(HttpResponse::BadRequest().json(ApiError::with_details(
+            "Unknown flavor family",
+            "INVALID_FLAVOR_FAMILY",
+            &format!("Expected one of: {}", FLAVOR_FAMILIES.join(", "))
+        ))),
+        _ => None,
+    }
+}
+
+// Best match handler - find ECS instances by CPU/RAM requirements
+pub async fn best_match(
+    data: web::Data<AppState>,
+    query: web::Query<BestMatchQuery>,
+) -> impl Responder {
+    if let Some(resp) = invalid_family(query.family.as_deref()) {
+        return resp;
+    }
+
+    let db = data.db.lock().unwrap();
+
+    match db.find_best_match(&query) {
+        Ok(matches) => HttpResponse::Ok().json(matches),
+        Err(e) => {
+            log::error!("Failed to find best match: {}", e);
+            HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to find matching instances"
+            }))
+        }
+    }
+}
+
+// Bulk sizing - map a workload inventory to flavors and disk types
+pub async fn sizing(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    body: web::Json<SizingRequest>,
+) -> impl Responder {
+    let actor = audit_actor(&req);
+    if let Some(resp) = invalid_family(body.family.as_deref()) {
+        return resp;
+    }
+    if body.workloads.is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "No workloads provided",
+            "SIZING_EMPTY"
+        ));
+    }
+
+    let db = data.db.lock().unwrap();
+
+    let (items, totals) = match size_workloads(&db, &body) {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!("Failed to size workloads: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to size workloads",
+                "SIZING_ERROR"
+            ));
+        }
+    };
+
+    // A quote missing some of the inventory would look complete, so none is created
+    if body.create_quote.is_some() && totals.unmatched > 0 {
+        return HttpResponse::BadRequest().json(ApiError::with_details(
+            "Some workloads have no matching flavor or disk",
+            "SIZING_UNMATCHED",
+            &format!("{} unmatched", totals.unmatched)
+        ));
+    }
+
+    let quote = match &body.create_quote {
+        Some(name) => {
+            let matched: Vec<CreateQuoteItem> = items.iter().filter_map(|w| w.item.clone()).collect();
+            let quote = CreateQuote {
+                name: name.clone(),
+                customer_id: None,
+                status: None,
+                tax_profile_id: None,
+            };
+            let result = db.transaction(|| {
+                let (quote, created) = db.create_quote_with_items(&quote, &matched)?;
+                if let Some(change) = AuditChange::quote(&actor, None, Some(&quote)) {
+                    db.record_audit(&change)?;
+                }
+                let changes: Vec<_> = created.iter().map(|item| (None, Some(item))).collect();
+                record_item_changes(&db, &actor, &changes)?;
+                Ok((quote, created))
+            });
+            match result {
+                Ok((quote, created)) => {
+                    for item in &created {
+                        data.events.publish(Event::item_created(item));
+                    }
+                    Some(quote)
+                }
+                Err(e) => {
+                    log::error!("Failed to create quote from sizing: {}", e);
+                    return HttpResponse::InternalServerError().json(ApiError::new(
+                        "Failed to create quote",
+                        "QUOTE_CREATE_ERROR"
+                    ));
+                }
+            }
+        }
+        None => None,
+    };
+
+    HttpResponse::Ok().json(SizingResponse { items, totals, quote })
+}
+
+// Blueprint handlers
+pub async fn get_blueprints(data: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(&data.blueprints)
+}
+
+pub async fn get_blueprint(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let id = path.into_inner();
+    match data.blueprints.iter().find(|b| b.id == id) {
+        Some(blueprint) => HttpResponse::Ok().json(blueprint),
+        None => HttpResponse::NotFound().json(ApiError::new(
+            "Blueprint not found",
+            "BLUEPRINT_NOT_FOUND"
+        )),
+    }
+}
+
+// Expand a blueprint into sized items, optionally adding them to a quote
+pub async fn expand_blueprint(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<ExpandBlueprint>,
+) -> impl Responder {
+    let id = path.into_inner();
+    let actor = audit_actor(&req);
+    let Some(blueprint) = data.blueprints.iter().find(|b| b.id == id) else {
+        return HttpResponse::NotFound().json(ApiError::new(
+            "Blueprint not found",
+            "BLUEPRINT_NOT_FOUND"
+        ));
+    };
+
+    let (parameters, requests) = match resolve_parameters(blueprint, &body.parameters).and_then(|parameters| {
+        sizing_requests(blueprint, &parameters, body.billing_mode, body.max_overshoot)
+            .map(|requests| (parameters, requests))
+    }) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ApiError::with_details(
+                "Invalid blueprint parameters",
+                "INVALID_BLUEPRINT_PARAMETERS",
+                &e
+            ))
+        }
+    };
+
+    let db = data.db.lock().unwrap();
+
+    if let Some(quote_id) = &body.quote_id {
+        if let Some(resp) = quote_missing(&db, quote_id) {
+            return resp;
+        }
+        if let Some(resp) = invalid_section(&db, quote_id, body.section_id.as_deref()) {
+            return resp;
+        }
+    }
+
+    let (items, totals) = match expand(&db, &requests) {
+        Ok(result) => result,
+        Err(e) => {
+            log::error!("Failed to expand blueprint: {}", e);
+            return HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to expand blueprint",
+                "BLUEPRINT_EXPAND_ERROR"
+            ));
+        }
+    };
+
+    let created = match &body.quote_id {
+        Some(quote_id) => {
+            if totals.unmatched > 0 {
+                return HttpResponse::BadRequest().json(ApiError::with_details(
+                    "Some blueprint instances have no matching flavor",
+                    "BLUEPRINT_UNMATCHED",
+                    &format!("{} unmatched", totals.unmatched)
+                ));
+            }
+            let section_id = body.section_id.as_deref().filter(|id| !id.is_empty());
+            let new_items: Vec<CreateQuoteItem> = items
+                .iter()
+                .filter_map(|w| w.item.clone())
+                .map(|item| CreateQuoteItem {
+                    section_id: section_id.map(str::to_string),
+                    ..item
+                })
+                .collect();
+            let result = db.transaction(|| {
+                let created = db.create_items(quote_id, &new_items)?;
+                let changes: Vec<_> = created.iter().map(|item| (None, Some(item))).collect();
+                record_item_changes(&db, &actor, &changes)?;
+                Ok(created)
+            });
+            match result {
+                Ok(created) => {
+                    for item in &created {
+                        data.events.publish(Event::item_created(item));
+                    }
+                    Some(created)
+                }
+                Err(e) => {
+                    log::error!("Failed to add blueprint items: {}", e);
+                    return HttpResponse::InternalServerError().json(ApiError::new(
+                        "Failed to add blueprint items",
+                        "BLUEPRINT_APPLY_ERROR"
+                    ));
+                }
+            }
+        }
+        None => None,
+    };
+
+    HttpResponse::Ok().json(BlueprintExpansion {
+        blueprint_id: blueprint.id.clone(),
+        parameters,
+        items,
+        totals,
+        created,
+    })
+}
+
+// Rightsizing - suggest smaller flavors from observed peak utilization
+pub async fn rightsize(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+    body: web::Json<RightsizeRequest>,
+) -> impl Responder {
+    let quote_id = path.into_inner();
+    if let Some(resp) = invalid_family(body.family.as_deref()) {
+        return resp;
+    }
+    if let Some(usage) = body.utilization.iter().find(|u| !u.is_valid()) {
+        return HttpResponse::BadRequest().json(ApiError::with_details(
+            "Peak utilization must be between 0 and 100 percent",
+            "INVALID_UTILIZATION",
+            &usage.key
+        ));
+    }
+
+    let db = data.db.lock().unwrap();
+
+    if let Some(resp) = quote_missing(&db, &quote_id) {
+        return resp;
+    }
+
+    match rightsize_items(&db, &quote_id, &body) {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            log::error!("Failed to rightsize quote: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to rightsize quote",
+                "RIGHTSIZE_ERROR"
+            ))
+        }
+    }
+}
+
+// Budget optimizer - cheapest catalog options that fit a monthly budget
+pub async fn optimize_budget(
+    data: web::Data<AppState>,
+    body: web::Json<OptimizeRequest>,
+) -> impl Responder {
+    if let Some(resp) = invalid_family(body.family.as_deref()) {
+        return resp;
+    }
+    if body.workloads.is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "No workloads provided",
+            "OPTIMIZE_EMPTY"
+        ));
+    }
+    if !body.monthly_budget.is_finite() || body.monthly_budget < 0.0 {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "Monthly budget must be a non-negative number",
+            "INVALID_BUDGET"
+        ));
+    }
+    if body.allowed_billing_modes().is_empty() {
+        return HttpResponse::BadRequest().json(ApiError::new(
+            "No billing modes left; set allow_commitments to use 1y or 3y",
+            "NO_BILLING_MODES"
+        ));
+    }
+
+    let db = data.db.lock().unwrap();
+
+    match optimize(&db, &body) {
+        Ok(result) => HttpResponse::Ok().json(result),
+        Err(e) => {
+            log::error!("Failed to optimize workloads: {}", e);
+            HttpResponse::InternalServerError().json(ApiError::new(
+                "Failed to optimize workloads",
+                "OPTIMIZE_ERROR"
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::events::EventBus;
+    use crate::testutil::database;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Mutex;
+
+    // App state over an empty in-memory database, with one quote
+    fn app_state() -> (AppState, String) {
+        let db = database();
+        let quote = db
+            .create_quote(&CreateQuote { name: "q".to_string(), customer_id: None, status: None, tax_profile_id: None })
+            .unwrap();
+        let state = AppState {
+            db: Mutex::new(db),
+            config: Config::from_env(),
+            crawl_running: AtomicBool::new(false),
+            blueprints: Vec::new(),
+            events: EventBus::default(),
+        };
+        (state, quote.id)
+    }
+
+    fn create_item(db: &Database, quote_id: &str, hostname: &str) -> QuoteItem {
+        let item = db
+            .create_item(quote_id, &CreateQuoteItem { hostname: Some(hostname.to_string()), ..Default::default() })
+            .unwrap();
+        record_item_change(db, "test", None, Some(&item)).unwrap();
+        item
+    }
+
+    // Rename an item without journaling it, as another writer would
+    fn rename(db: &Database, item_id: &str, hostname: &str) {
+        let update: UpdateQuoteItem = serde_json::from_value(serde_json::json!({ "hostname": hostname })).unwrap();
+        db.update_item(item_id, &update).unwrap();
+    }
+
+    fn hostname(data: &AppState, item_id: &str) -> Option<String> {
+        data.db.lock().unwrap().get_item(item_id).unwrap().and_then(|item| item.hostname)
+    }
+
+    #[test]
+    fn undo_and_redo_restore_journaled_states() {
+        let (data, quote_id) = app_state();
+        let req = TestRequest::default().to_http_request();
+        let item = {
+            let db = data.db.lock().unwrap();
+            let item = create_item(&db, &quote_id, "web-1");
+            let before = db.get_item(&item.id).unwrap();
+            rename(&db, &item.id, "web-2");
+            let after = db.get_item(&item.id).unwrap();
+            record_item_change(&db, "test", before.as_ref(), after.as_ref()).unwrap();
+            item
+        };
+
+        assert_eq!(step_journal(&req, &data, &quote_id, true).status(), StatusCode::OK);
+        assert_eq!(hostname(&data, &item.id).as_deref(), Some("web-1"));
+        // The undo bumped the item's version; redo still applies
+        assert_eq!(step_journal(&req, &data, &quote_id, false).status(), StatusCode::OK);
+        assert_eq!(hostname(&data, &item.id).as_deref(), Some("web-2"));
+    }
+
+    #[test]
+    fn undo_is_refused_when_an_item_changed_since() {
+        let (data, quote_id) = app_state();
+        let req = TestRequest::default().to_http_request();
+        let item = {
+            let db = data.db.lock().unwrap();
+            let item = create_item(&db, &quote_id, "web-1");
+            rename(&db, &item.id, "edited");
+            item
+        };
+
+        assert_eq!(step_journal(&req, &data, &quote_id, true).status(), StatusCode::CONFLICT);
+        assert_eq!(hostname(&data, &item.id).as_deref(), Some("edited"));
+        assert_eq!(data.db.lock().unwrap().journal_counts(&quote_id).unwrap(), (1, 0));
+    }
+
+    #[test]
+    fn refused_steps_write_back_none_of_their_items() {
+        let (data, quote_id) = app_state();
+        let req = TestRequest::default().to_http_request();
+        let (first, second) = {
+            let db = data.db.lock().unwrap();
+            let first = create_item(&db, &quote_id, "a");
+            let second = create_item(&db, &quote_id, "b");
+            let before: Vec<_> = [&first, &second].iter().map(|i| db.get_item(&i.id).unwrap()).collect();
+            rename(&db, &first.id, "a2");
+            rename(&db, &second.id, "b2");
+            let after: Vec<_> = [&first, &second].iter().map(|i| db.get_item(&i.id).unwrap()).collect();
+            record_item_changes(
+                &db,
+                "test",
+                &[(before[0].as_ref(), after[0].as_ref()), (before[1].as_ref(), after[1].as_ref())],
+            )
+            .unwrap();
+            // Only the second item changes after the step was recorded
+            rename(&db, &second.id, "b3");
+            (first, second)
+        };
+
+        assert_eq!(step_journal(&req, &data, &quote_id, true).status(), StatusCode::CONFLICT);
+        assert_eq!(hostname(&data, &first.id).as_deref(), Some("a2"));
+        assert_eq!(hostname(&data, &second.id).as_deref(), Some("b3"));
+    }
+}
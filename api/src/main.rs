@@ -1,15 +1,36 @@
+mod adjustments;
+mod blueprints;
+mod config;
 mod db;
+mod events;
+mod export;
 mod handlers;
 mod models;
+mod optimizer;
+mod pricing;
+mod scheduler;
+mod sizing;
+#[cfg(test)]
+mod testutil;
 
 use actix_cors::Cors;
 use actix_web::{middleware::Logger, web, App, HttpServer};
+use std::sync::atomic::AtomicBool;
 use std::sync::Mutex;
 
+use config::Config;
 use db::Database;
+use events::EventBus;
+use models::Blueprint;
 
 pub struct AppState {
     pub db: Mutex<Database>,
+    pub config: Config,
+    pub crawl_running: AtomicBool,
+    // Loaded once at startup
+    pub blueprints: Vec<Blueprint>,
+    // Server-sent event subscribers
+    pub events: EventBus,
 }
 
 #[actix_web::main]
@@ -18,20 +39,33 @@ async fn main() -> std::io::Result<()> {
 
     log::info!("Starting Quotator API on port 3848");
 
-    // Initialize database (check env var, default to /app/data for Docker)
-    let db_path = std::env::var("DB_PATH").unwrap_or_else(|_| "/app/data/quotator.db".to_string());
-    log::info!("Using database: {}", db_path);
-    let db = Database::new(&db_path).expect("Failed to initialize database");
+    let config = Config::from_env();
+    log::info!("Using database: {}", config.db_path);
+    log::info!("Using crawler: {}", config.crawler_url);
+    let db = Database::new(&config.db_path).expect("Failed to initialize database");
+    let blueprints = blueprints::load_blueprints(&config.blueprints_dir);
 
     let app_state = web::Data::new(AppState {
         db: Mutex::new(db),
+        config,
+        crawl_running: AtomicBool::new(false),
+        blueprints,
+        events: EventBus::default(),
     });
 
+    // Scheduled pricing refresh (no-op when CRAWL_SCHEDULE is unset)
+    tokio::spawn(scheduler::run(app_state.clone()));
+    // Permanent removal of expired trash (no-op when TRASH_RETENTION_DAYS is 0)
+    tokio::spawn(scheduler::purge_trash(app_state.clone()));
+    // Pings event subscribers so idle streams stay open
+    tokio::spawn(scheduler::event_keepalive(app_state.clone()));
+
     HttpServer::new(move || {
         let cors = Cors::default()
             .allow_any_origin()
             .allow_any_method()
             .allow_any_header()
+            .expose_headers(vec![actix_web::http::header::ETAG])
             .max_age(3600);
 
         App::new()
@@ -42,8 +76,39 @@ async fn main() -> std::io::Result<()> {
             .route("/flavors", web::get().to(handlers::get_flavors))
             .route("/flavors/match", web::get().to(handlers::best_match))
             .route("/disks", web::get().to(handlers::get_disk_types))
+            .route("/bandwidth", web::get().to(handlers::get_bandwidth_types))
+            .route("/images", web::get().to(handlers::get_images))
             .route("/pricing", web::get().to(handlers::get_all_pricing))
+            .route("/products", web::get().to(handlers::get_products))
+            .route("/products", web::post().to(handlers::create_product))
+            .route("/products/{id}", web::put().to(handlers::update_product))
+            .route("/products/{id}", web::delete().to(handlers::delete_product))
+            .route("/customers", web::get().to(handlers::get_customers))
+            .route("/customers", web::post().to(handlers::create_customer))
+            .route("/customers/{id}", web::get().to(handlers::get_customer))
+            .route("/customers/{id}", web::put().to(handlers::update_customer))
+            .route("/customers/{id}", web::delete().to(handlers::delete_customer))
+            .route("/customers/{id}/summary", web::get().to(handlers::get_customer_summary))
+            .route("/tax-profiles", web::get().to(handlers::get_tax_profiles))
+            .route("/tax-profiles", web::post().to(handlers::create_tax_profile))
+            .route("/tax-profiles/{id}", web::put().to(handlers::update_tax_profile))
+            .route("/tax-profiles/{id}", web::delete().to(handlers::delete_tax_profile))
+            .route("/backup-policies", web::get().to(handlers::get_backup_policies))
+            .route("/backup-policies", web::post().to(handlers::create_backup_policy))
+            .route("/backup-policies/{id}", web::put().to(handlers::update_backup_policy))
+            .route("/backup-policies/{id}", web::delete().to(handlers::delete_backup_policy))
+            .route("/templates", web::get().to(handlers::get_templates))
+            .route("/templates", web::post().to(handlers::create_template))
+            .route("/templates/{id}", web::get().to(handlers::get_template))
+            .route("/templates/{id}", web::put().to(handlers::update_template))
+            .route("/templates/{id}", web::delete().to(handlers::delete_template))
+            .route("/blueprints", web::get().to(handlers::get_blueprints))
+            .route("/blueprints/{id}", web::get().to(handlers::get_blueprint))
+            .route("/blueprints/{id}/expand", web::post().to(handlers::expand_blueprint))
+            .route("/trash", web::get().to(handlers::get_trash))
             .route("/crawl", web::post().to(handlers::trigger_crawl))
+            .route("/sizing", web::post().to(handlers::sizing))
+            .route("/optimize", web::post().to(handlers::optimize_budget))
             // Quote endpoints
             .route("/quotes", web::get().to(handlers::get_quotes_all))  // Legacy: all quotes
             .route("/quotes/paginated", web::get().to(handlers::get_quotes))  // New: paginated
@@ -51,11 +116,38 @@ async fn main() -> std::io::Result<()> {
             .route("/quotes/{id}", web::get().to(handlers::get_quote))
             .route("/quotes/{id}", web::put().to(handlers::update_quote))
             .route("/quotes/{id}", web::delete().to(handlers::delete_quote))
+            .route("/quotes/{id}/totals", web::get().to(handlers::get_quote_totals))
+            .route("/quotes/{id}/export", web::get().to(handlers::export_quote_csv))
+            .route("/quotes/{id}/tco", web::get().to(handlers::get_quote_tco))
+            .route("/quotes/{id}/rightsize", web::post().to(handlers::rightsize))
+            .route("/quotes/{id}/audit", web::get().to(handlers::get_quote_audit))
+            .route("/quotes/{id}/restore", web::post().to(handlers::restore_quote))
+            .route("/quotes/{id}/undo", web::post().to(handlers::undo_quote))
+            .route("/quotes/{id}/redo", web::post().to(handlers::redo_quote))
+            .route("/quotes/{id}/apply-template", web::post().to(handlers::apply_template))
+            .route("/quotes/{id}/events", web::get().to(handlers::quote_events))
             // Quote items endpoints
+            .route("/quotes/{quote_id}/sections", web::get().to(handlers::get_sections))
+            .route("/quotes/{quote_id}/sections", web::post().to(handlers::create_section))
+            .route("/quotes/{quote_id}/sections/order", web::put().to(handlers::reorder_sections))
+            .route("/quotes/{quote_id}/sections/{section_id}", web::put().to(handlers::update_section))
+            .route("/quotes/{quote_id}/sections/{section_id}", web::delete().to(handlers::delete_section))
+            .route("/quotes/{quote_id}/comments", web::get().to(handlers::get_comments))
+            .route("/quotes/{quote_id}/comments", web::post().to(handlers::create_comment))
+            .route("/quotes/{quote_id}/comments/{comment_id}", web::put().to(handlers::update_comment))
+            .route("/quotes/{quote_id}/comments/{comment_id}", web::delete().to(handlers::delete_comment))
+            .route("/quotes/{quote_id}/adjustments", web::get().to(handlers::get_adjustments))
+            .route("/quotes/{quote_id}/adjustments", web::post().to(handlers::create_adjustment))
+            .route("/quotes/{quote_id}/adjustments/{adjustment_id}", web::put().to(handlers::update_adjustment))
+            .route("/quotes/{quote_id}/adjustments/{adjustment_id}", web::delete().to(handlers::delete_adjustment))
             .route("/quotes/{quote_id}/items", web::get().to(handlers::get_items))
             .route("/quotes/{quote_id}/items", web::post().to(handlers::create_item))
+            .route("/quotes/{quote_id}/items/order", web::put().to(handlers::reorder_items))
             .route("/quotes/{quote_id}/items/{item_id}", web::put().to(handlers::update_item))
             .route("/quotes/{quote_id}/items/{item_id}", web::delete().to(handlers::delete_item))
+            .route("/quotes/{quote_id}/items/{item_id}/restore", web::post().to(handlers::restore_item))
+            // Global server-sent events (crawls, price updates)
+            .route("/events", web::get().to(handlers::global_events))
             // Health check
             .route("/health", web::get().to(handlers::health_check))
     })
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Flavor {
@@ -23,12 +24,407 @@ pub struct DiskType {
     pub created_at: String,
 }
 
+// EIP and bandwidth pricing, crawled alongside disk types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BandwidthType {
+    pub id: String,
+    pub name: String,
+    pub price_per_ip_month: f64,
+    // Fixed bandwidth, per Mbps per month
+    pub price_per_mbps: f64,
+    // Traffic-billed bandwidth, per GB transferred
+    pub price_per_gb: f64,
+    pub region: String,
+    pub created_at: String,
+}
+
+impl BandwidthType {
+    pub fn monthly_price(&self, eip_count: Option<i32>, mbps: Option<f64>, traffic_gb: Option<f64>) -> f64 {
+        eip_count.unwrap_or(0) as f64 * self.price_per_ip_month
+            + mbps.unwrap_or(0.0) * self.price_per_mbps
+            + traffic_gb.unwrap_or(0.0) * self.price_per_gb
+    }
+}
+
+// OS image licence pricing, crawled alongside flavors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Image {
+    pub id: String,
+    pub name: String,
+    // "linux", "windows", "rhel", "suse"
+    pub os_family: String,
+    // Licence charge per vCPU per hour; 0 for free images
+    pub price_per_vcpu_hour: f64,
+    pub region: String,
+    pub created_at: String,
+}
+
+// Generic SKU catalog for services beyond ECS and EVS (OBS, RDS, ELB, CBR, ...).
+// Public IPs and bandwidth are priced from the bandwidth catalog instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PricingUnit {
+    // Quantity is GB stored per month
+    #[serde(rename = "per_gb_month")]
+    GbMonth,
+    // Quantity is instances, billed for the item's usage hours
+    #[serde(rename = "per_hour")]
+    Hour,
+    // Quantity is requests per month
+    #[serde(rename = "per_request")]
+    Request,
+}
+
+impl PricingUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PricingUnit::GbMonth => "per_gb_month",
+            PricingUnit::Hour => "per_hour",
+            PricingUnit::Request => "per_request",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "per_gb_month" => Some(PricingUnit::GbMonth),
+            "per_hour" => Some(PricingUnit::Hour),
+            "per_request" => Some(PricingUnit::Request),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Product {
+    pub id: String,
+    // Product family, e.g. "obs", "rds", "elb", "cbr"
+    pub category: String,
+    pub name: String,
+    pub unit: PricingUnit,
+    pub price: f64,
+    pub region: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProduct {
+    pub id: Option<String>,
+    pub category: String,
+    pub name: String,
+    pub unit: PricingUnit,
+    pub price: f64,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProduct {
+    pub category: Option<String>,
+    pub name: Option<String>,
+    pub unit: Option<PricingUnit>,
+    pub price: Option<f64>,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductQuery {
+    pub category: Option<String>,
+}
+
+// Backup policy for item disks: a full copy plus incrementals retained for
+// retention_days, each holding change_rate_pct of the disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupPolicy {
+    pub id: String,
+    pub name: String,
+    pub retention_days: i32,
+    pub change_rate_pct: f64,
+    // Backup vault price per GB-month
+    pub price_per_gb: f64,
+    pub region: String,
+    pub created_at: String,
+}
+
+impl BackupPolicy {
+    pub fn vault_size_gb(disk_gb: f64, retention_days: i32, change_rate_pct: f64) -> f64 {
+        disk_gb * (1.0 + retention_days.max(0) as f64 * change_rate_pct.max(0.0) / 100.0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBackupPolicy {
+    pub id: Option<String>,
+    pub name: String,
+    pub retention_days: i32,
+    pub change_rate_pct: f64,
+    pub price_per_gb: f64,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBackupPolicy {
+    pub name: Option<String>,
+    pub retention_days: Option<i32>,
+    pub change_rate_pct: Option<f64>,
+    pub price_per_gb: Option<f64>,
+    pub region: Option<String>,
+}
+
+// Negotiated price adjustment: a partner discount or a services markup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentKind {
+    Discount,
+    Markup,
+}
+
+impl AdjustmentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjustmentKind::Discount => "discount",
+            AdjustmentKind::Markup => "markup",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "discount" => Some(AdjustmentKind::Discount),
+            "markup" => Some(AdjustmentKind::Markup),
+            _ => None,
+        }
+    }
+
+    // Direction of the adjustment relative to the list price
+    pub fn sign(&self) -> f64 {
+        match self {
+            AdjustmentKind::Discount => -1.0,
+            AdjustmentKind::Markup => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentType {
+    // Value is a percentage of the in-scope cost
+    Percent,
+    // Value is a monthly amount, spread over the in-scope cost
+    Fixed,
+}
+
+impl AdjustmentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AdjustmentType::Percent => "percent",
+            AdjustmentType::Fixed => "fixed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "percent" => Some(AdjustmentType::Percent),
+            "fixed" => Some(AdjustmentType::Fixed),
+            _ => None,
+        }
+    }
+}
+
+// Quote-level when item_id is None; applies to every cost category when category is None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adjustment {
+    pub id: String,
+    pub quote_id: String,
+    pub item_id: Option<String>,
+    pub kind: AdjustmentKind,
+    pub amount_type: AdjustmentType,
+    pub value: f64,
+    // "compute", "licence", "storage", "backup", "network", or a SKU category
+    pub category: Option<String>,
+    pub description: Option<String>,
+    pub created_at: String,
+}
+
+// Description of the adjustment that carries a customer's default discount
+pub const CUSTOMER_DISCOUNT: &str = "Customer default discount";
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAdjustment {
+    pub item_id: Option<String>,
+    pub kind: AdjustmentKind,
+    pub amount_type: AdjustmentType,
+    pub value: f64,
+    pub category: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateAdjustment {
+    pub kind: Option<AdjustmentKind>,
+    pub amount_type: Option<AdjustmentType>,
+    pub value: Option<f64>,
+    pub category: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Customer {
+    pub id: String,
+    pub name: String,
+    pub tax_id: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    // ISO 4217 code; informational, prices are not converted
+    pub currency: String,
+    // Percentage discount added to new quotes for this customer
+    pub default_discount_pct: Option<f64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCustomer {
+    pub name: String,
+    pub tax_id: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub currency: Option<String>,
+    pub default_discount_pct: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCustomer {
+    pub name: Option<String>,
+    pub tax_id: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub currency: Option<String>,
+    pub default_discount_pct: Option<f64>,
+}
+
+// Value of one quote in a customer summary
+#[derive(Debug, Serialize)]
+pub struct CustomerQuoteValue {
+    pub quote_id: String,
+    pub name: String,
+    pub status: QuoteStatus,
+    pub net_monthly: f64,
+    pub gross_monthly: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CustomerSummary {
+    pub customer: Customer,
+    pub quote_count: u32,
+    // Open quotes are drafts and quotes sent to the customer
+    pub open_quote_count: u32,
+    pub open_net_monthly: f64,
+    pub open_gross_monthly: f64,
+    pub open_gross_yearly: f64,
+    pub quotes: Vec<CustomerQuoteValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuoteStatus {
+    #[default]
+    Draft,
+    Sent,
+    Accepted,
+    Rejected,
+}
+
+impl QuoteStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteStatus::Draft => "draft",
+            QuoteStatus::Sent => "sent",
+            QuoteStatus::Accepted => "accepted",
+            QuoteStatus::Rejected => "rejected",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "draft" => Some(QuoteStatus::Draft),
+            "sent" => Some(QuoteStatus::Sent),
+            "accepted" => Some(QuoteStatus::Accepted),
+            "rejected" => Some(QuoteStatus::Rejected),
+            _ => None,
+        }
+    }
+
+    // Still awaiting a customer decision
+    pub fn is_open(&self) -> bool {
+        matches!(self, QuoteStatus::Draft | QuoteStatus::Sent)
+    }
+}
+
+// Tax profile assigned to a quote, e.g. KDV 20%. Inclusive profiles treat
+// prices as already containing the tax.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxProfile {
+    pub id: String,
+    pub label: String,
+    // Percentage, e.g. 20 for 20%
+    pub rate: f64,
+    pub inclusive: bool,
+    pub created_at: String,
+}
+
+impl TaxProfile {
+    // Catalog amount net of tax: inclusive prices already contain it
+    pub fn net_of(&self, amount: f64) -> f64 {
+        if self.inclusive {
+            amount / (1.0 + self.rate / 100.0)
+        } else {
+            amount
+        }
+    }
+
+    // Tax charged on a net amount
+    pub fn tax_on(&self, net: f64) -> f64 {
+        net * self.rate / 100.0
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTaxProfile {
+    pub id: Option<String>,
+    pub label: String,
+    pub rate: f64,
+    pub inclusive: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTaxProfile {
+    pub label: Option<String>,
+    pub rate: Option<f64>,
+    pub inclusive: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Quote {
     pub id: String,
     pub name: String,
+    pub customer_id: Option<String>,
+    pub status: QuoteStatus,
+    pub tax_profile_id: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    // Set while the quote is in the trash
+    pub deleted_at: Option<String>,
+    // Bumped on every update, sent as the ETag and checked against If-Match
+    pub version: i64,
+}
+
+// Quote in the quote lists, with its comment counts
+#[derive(Debug, Serialize)]
+pub struct QuoteListEntry {
+    #[serde(flatten)]
+    pub quote: Quote,
+    pub comment_count: u32,
+    pub open_comment_count: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,21 +443,325 @@ pub struct QuoteItem {
     pub hostname: Option<String>,
     pub code_number: Option<String>,
     pub description: Option<String>,
+    pub billing_mode: BillingMode,
+    // Runtime per month for hourly items; overrides usage_schedule when set
+    pub usage_hours_per_month: Option<f64>,
+    pub usage_schedule: Option<UsageSchedule>,
+    // Catalog SKU, with name/unit/price captured when the item was added
+    pub sku_id: Option<String>,
+    pub sku_category: Option<String>,
+    pub sku_name: Option<String>,
+    pub sku_unit: Option<PricingUnit>,
+    pub sku_price: Option<f64>,
+    pub sku_quantity: Option<f64>,
+    // Elastic IPs with fixed (Mbps) and/or traffic-billed (GB per month) bandwidth
+    pub bandwidth_type_id: Option<String>,
+    pub bandwidth_type_name: Option<String>,
+    pub eip_count: Option<i32>,
+    pub bandwidth_mbps: Option<f64>,
+    pub traffic_gb: Option<f64>,
+    pub bandwidth_price: Option<f64>,
+    // OS image, with licence price per vCPU-hour captured when selected
+    pub os_image: Option<String>,
+    pub os_image_name: Option<String>,
+    pub os_licence_price: Option<f64>,
+    // Disk backup policy, with retention, change rate and vault price captured when attached
+    pub backup_policy_id: Option<String>,
+    pub backup_policy_name: Option<String>,
+    pub backup_retention_days: Option<i32>,
+    pub backup_change_rate_pct: Option<f64>,
+    pub backup_price_per_gb: Option<f64>,
+    pub section_id: Option<String>,
+    // Display order within the quote
+    pub position: i32,
     pub created_at: String,
     pub updated_at: String,
+    // Set while the item is in the trash
+    pub deleted_at: Option<String>,
+    // Bumped on every update, checked against If-Match. Journal snapshots taken
+    // before versioning have none.
+    #[serde(default)]
+    pub version: i64,
+}
+
+// Named group of items within a quote, e.g. prod, staging, DR
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuoteSection {
+    pub id: String,
+    pub quote_id: String,
+    pub name: String,
+    pub position: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSection {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateSection {
+    pub name: Option<String>,
+}
+
+// Full ordering of a quote's sections
+#[derive(Debug, Deserialize)]
+pub struct SectionOrder {
+    pub section_ids: Vec<String>,
+}
+
+// Full ordering of a quote's items
+#[derive(Debug, Deserialize)]
+pub struct ItemOrder {
+    pub item_ids: Vec<String>,
+}
+
+// Review comment on a quote, or on one of its items when item_id is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: String,
+    pub quote_id: String,
+    pub item_id: Option<String>,
+    pub author: String,
+    pub body: String,
+    pub resolved: bool,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateComment {
+    pub item_id: Option<String>,
+    pub body: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateComment {
+    pub body: Option<String>,
+    pub resolved: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentQuery {
+    pub item_id: Option<String>,
+    pub resolved: Option<bool>,
+}
+
+// Kind of change recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Restore,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Restore => "restore",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "create" => Some(AuditAction::Create),
+            "update" => Some(AuditAction::Update),
+            "delete" => Some(AuditAction::Delete),
+            "restore" => Some(AuditAction::Restore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    Quote,
+    Item,
+    Section,
+    Adjustment,
+    Comment,
+}
+
+impl AuditEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditEntity::Quote => "quote",
+            AuditEntity::Item => "item",
+            AuditEntity::Section => "section",
+            AuditEntity::Adjustment => "adjustment",
+            AuditEntity::Comment => "comment",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "quote" => Some(AuditEntity::Quote),
+            "item" => Some(AuditEntity::Item),
+            "section" => Some(AuditEntity::Section),
+            "adjustment" => Some(AuditEntity::Adjustment),
+            "comment" => Some(AuditEntity::Comment),
+            _ => None,
+        }
+    }
+}
+
+// One change to a quote or one of its items, sections or adjustments. Entries
+// outlive the quote.
+#[derive(Debug, Serialize)]
+pub struct AuditEntry {
+    pub id: String,
+    pub quote_id: String,
+    pub entity: AuditEntity,
+    pub entity_id: String,
+    pub action: AuditAction,
+    // Full entity state; no before on create, no after on delete
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub actor: String,
+    pub created_at: String,
+}
+
+// A change to record, before it gets an id and timestamp
+#[derive(Debug)]
+pub struct AuditChange {
+    pub quote_id: String,
+    pub entity: AuditEntity,
+    pub entity_id: String,
+    pub action: AuditAction,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub actor: String,
+}
+
+impl AuditChange {
+    fn new<T: Serialize>(
+        entity: AuditEntity,
+        quote_id: &str,
+        entity_id: &str,
+        before: Option<&T>,
+        after: Option<&T>,
+        actor: &str,
+    ) -> Self {
+        let action = match (before, after) {
+            (None, _) => AuditAction::Create,
+            (_, None) => AuditAction::Delete,
+            _ => AuditAction::Update,
+        };
+        AuditChange {
+            quote_id: quote_id.to_string(),
+            entity,
+            entity_id: entity_id.to_string(),
+            action,
+            before: before.map(|b| serde_json::json!(b)),
+            after: after.map(|a| serde_json::json!(a)),
+            actor: actor.to_string(),
+        }
+    }
+
+    // The action follows from which states are present; None when neither is,
+    // i.e. the quote didn't exist and nothing changed
+    pub fn quote(actor: &str, before: Option<&Quote>, after: Option<&Quote>) -> Option<Self> {
+        let quote = before.or(after)?;
+        Some(Self::new(AuditEntity::Quote, &quote.id, &quote.id, before, after, actor))
+    }
+
+    pub fn item(actor: &str, before: Option<&QuoteItem>, after: Option<&QuoteItem>) -> Option<Self> {
+        let item = before.or(after)?;
+        Some(Self::new(AuditEntity::Item, &item.quote_id, &item.id, before, after, actor))
+    }
+
+    pub fn section(actor: &str, before: Option<&QuoteSection>, after: Option<&QuoteSection>) -> Option<Self> {
+        let section = before.or(after)?;
+        Some(Self::new(AuditEntity::Section, &section.quote_id, &section.id, before, after, actor))
+    }
+
+    pub fn adjustment(actor: &str, before: Option<&Adjustment>, after: Option<&Adjustment>) -> Option<Self> {
+        let adjustment = before.or(after)?;
+        Some(Self::new(AuditEntity::Adjustment, &adjustment.quote_id, &adjustment.id, before, after, actor))
+    }
+
+    pub fn comment(actor: &str, before: Option<&Comment>, after: Option<&Comment>) -> Option<Self> {
+        let comment = before.or(after)?;
+        Some(Self::new(AuditEntity::Comment, &comment.quote_id, &comment.id, before, after, actor))
+    }
+}
+
+// Invertible item change in a quote's undo/redo journal. States are full item
+// snapshots; None means the item didn't exist (or was in the trash).
+#[derive(Debug, Serialize)]
+pub struct JournalEntry {
+    pub id: String,
+    pub quote_id: String,
+    pub seq: i64,
+    pub item_id: String,
+    pub before: Option<QuoteItem>,
+    pub after: Option<QuoteItem>,
+    // Undone entries form the redo stack until a new change clears them
+    pub undone: bool,
+    pub created_at: String,
+    // Entries recorded by one request (a template, a reorder) are one undo step
+    pub group_id: String,
+}
+
+// Result of an undo or redo: the operations stepped over and the items now in
+// place, leaving out items the step removed
+#[derive(Debug, Serialize)]
+pub struct JournalStep {
+    pub group_id: String,
+    pub operations: Vec<JournalEntry>,
+    pub items: Vec<QuoteItem>,
+    pub undo_available: u32,
+    pub redo_available: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+// Soft-deleted quotes, and deleted items of quotes that are not themselves deleted
+#[derive(Debug, Serialize)]
+pub struct Trash {
+    pub quotes: Vec<Quote>,
+    pub items: Vec<QuoteItem>,
+}
+
+// Everything the pricing engine needs to price a quote
+#[derive(Debug)]
+pub struct QuotePricing {
+    pub items: Vec<QuoteItem>,
+    pub adjustments: Vec<Adjustment>,
+    pub sections: Vec<QuoteSection>,
+    pub tax: Option<TaxProfile>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateQuote {
     pub name: String,
+    pub customer_id: Option<String>,
+    pub status: Option<QuoteStatus>,
+    pub tax_profile_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UpdateQuote {
     pub name: Option<String>,
+    pub customer_id: Option<String>,
+    pub status: Option<QuoteStatus>,
+    pub tax_profile_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CreateQuoteItem {
     pub flavor_id: Option<String>,
     pub flavor_name: Option<String>,
@@ -75,6 +775,28 @@ pub struct CreateQuoteItem {
     pub hostname: Option<String>,
     pub code_number: Option<String>,
     pub description: Option<String>,
+    pub billing_mode: Option<BillingMode>,
+    pub usage_hours_per_month: Option<f64>,
+    pub usage_schedule: Option<UsageSchedule>,
+    pub sku_id: Option<String>,
+    // Set from the catalog when sku_id is given; client values are discarded
+    #[serde(flatten)]
+    pub sku: SkuDetails,
+    pub sku_quantity: Option<f64>,
+    pub bandwidth_type_id: Option<String>,
+    pub bandwidth_type_name: Option<String>,
+    pub eip_count: Option<i32>,
+    pub bandwidth_mbps: Option<f64>,
+    pub traffic_gb: Option<f64>,
+    pub bandwidth_price: Option<f64>,
+    pub os_image: Option<String>,
+    pub os_image_name: Option<String>,
+    pub os_licence_price: Option<f64>,
+    pub backup_policy_id: Option<String>,
+    // Set from the policy when backup_policy_id is given; client values are discarded
+    #[serde(flatten)]
+    pub backup: BackupDetails,
+    pub section_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -91,18 +813,667 @@ pub struct UpdateQuoteItem {
     pub hostname: Option<String>,
     pub code_number: Option<String>,
     pub description: Option<String>,
+    pub billing_mode: Option<BillingMode>,
+    pub usage_hours_per_month: Option<f64>,
+    pub usage_schedule: Option<UsageSchedule>,
+    pub sku_id: Option<String>,
+    // Set from the catalog when sku_id is given; client values are discarded
+    #[serde(flatten)]
+    pub sku: SkuDetails,
+    pub sku_quantity: Option<f64>,
+    pub bandwidth_type_id: Option<String>,
+    pub bandwidth_type_name: Option<String>,
+    pub eip_count: Option<i32>,
+    pub bandwidth_mbps: Option<f64>,
+    pub traffic_gb: Option<f64>,
+    pub bandwidth_price: Option<f64>,
+    pub os_image: Option<String>,
+    pub os_image_name: Option<String>,
+    pub os_licence_price: Option<f64>,
+    pub backup_policy_id: Option<String>,
+    // Set from the policy when backup_policy_id is given; client values are discarded
+    #[serde(flatten)]
+    pub backup: BackupDetails,
+    pub section_id: Option<String>,
+}
+
+// SKU details captured on an item when it references a catalog product
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SkuDetails {
+    #[serde(rename = "sku_category")]
+    pub category: Option<String>,
+    #[serde(rename = "sku_name")]
+    pub name: Option<String>,
+    #[serde(rename = "sku_unit")]
+    pub unit: Option<PricingUnit>,
+    #[serde(rename = "sku_price")]
+    pub price: Option<f64>,
+}
+
+impl From<&Product> for SkuDetails {
+    fn from(product: &Product) -> Self {
+        Self {
+            category: Some(product.category.clone()),
+            name: Some(product.name.clone()),
+            unit: Some(product.unit),
+            price: Some(product.price),
+        }
+    }
+}
+
+// Backup settings captured on an item when it references a backup policy
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupDetails {
+    #[serde(rename = "backup_policy_name")]
+    pub policy_name: Option<String>,
+    #[serde(rename = "backup_retention_days")]
+    pub retention_days: Option<i32>,
+    #[serde(rename = "backup_change_rate_pct")]
+    pub change_rate_pct: Option<f64>,
+    #[serde(rename = "backup_price_per_gb")]
+    pub price_per_gb: Option<f64>,
+}
+
+impl From<&BackupPolicy> for BackupDetails {
+    fn from(policy: &BackupPolicy) -> Self {
+        Self {
+            policy_name: Some(policy.name.clone()),
+            retention_days: Some(policy.retention_days),
+            change_rate_pct: Some(policy.change_rate_pct),
+            price_per_gb: Some(policy.price_per_gb),
+        }
+    }
+}
+
+// Reusable set of item definitions, e.g. a 3-tier web app or a k8s cluster
+#[derive(Debug, Clone, Serialize)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub items: Vec<TemplateItem>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// Item definition within a template. Only catalog ids are stored; prices are
+// resolved from the catalog when the template is applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateItem {
+    pub flavor_id: Option<String>,
+    pub disk_type_id: Option<String>,
+    pub disk_size_gb: Option<i32>,
+    // "{n}" is replaced by the instance number, starting at 1
+    pub hostname_pattern: Option<String>,
+    pub description: Option<String>,
+    pub billing_mode: Option<BillingMode>,
+    // Number of identical items to create, 1 when unset
+    pub count: Option<i32>,
+}
+
+// Upper bound on TemplateItem::count, so one template can't flood a quote
+pub const MAX_TEMPLATE_ITEM_COUNT: i32 = 100;
+
+impl TemplateItem {
+    // Quote items for this definition, priced from the given catalog entries
+    pub fn instantiate(
+        &self,
+        flavor: Option<&Flavor>,
+        disk: Option<&DiskType>,
+        billing_mode: Option<BillingMode>,
+        section_id: Option<&str>,
+    ) -> Vec<CreateQuoteItem> {
+        let disk_size = disk.and(self.disk_size_gb);
+        (1..=self.count.unwrap_or(1))
+            .map(|n| CreateQuoteItem {
+                flavor_id: flavor.map(|f| f.id.clone()),
+                flavor_name: flavor.map(|f| f.name.clone()),
+                vcpus: flavor.map(|f| f.vcpus),
+                ram_gb: flavor.map(|f| f.ram_gb),
+                flavor_price: flavor.map(|f| f.price_hourly),
+                disk_type_id: disk.map(|d| d.id.clone()),
+                disk_type_name: disk.map(|d| d.name.clone()),
+                disk_size_gb: disk_size,
+                disk_price: disk.zip(disk_size).map(|(d, gb)| d.price_per_gb * gb as f64),
+                hostname: self.hostname_pattern.as_ref().map(|p| p.replace("{n}", &n.to_string())),
+                description: self.description.clone(),
+                billing_mode: billing_mode.or(self.billing_mode),
+                section_id: section_id.map(str::to_string),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTemplate {
+    pub name: String,
+    pub description: Option<String>,
+    pub items: Vec<TemplateItem>,
+}
+
+// Items, when given, replace the template's item definitions
+#[derive(Debug, Deserialize)]
+pub struct UpdateTemplate {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub items: Option<Vec<TemplateItem>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApplyTemplate {
+    pub template_id: String,
+    // Section to place the new items in
+    pub section_id: Option<String>,
+    // Overrides the billing mode of every template item
+    pub billing_mode: Option<BillingMode>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct PricingData {
     pub flavors: Vec<Flavor>,
     pub disk_types: Vec<DiskType>,
+    pub bandwidth_types: Vec<BandwidthType>,
+    pub images: Vec<Image>,
 }
 
-#[derive(Debug, Deserialize)]
+// Billed hours in a month, matching the crawler's monthly price
+pub const HOURS_PER_MONTH: f64 = 720.0;
+
+// Billing mode used to price and rank flavors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BillingMode {
+    // Pay-per-use, billed for the hours actually run
+    #[serde(rename = "hourly")]
+    Hourly,
+    #[default]
+    #[serde(rename = "monthly")]
+    Monthly,
+    #[serde(rename = "1y", alias = "yearly1")]
+    Yearly1,
+    #[serde(rename = "3y", alias = "yearly3")]
+    Yearly3,
+}
+
+impl BillingMode {
+    pub const ALL: [BillingMode; 4] = [
+        BillingMode::Hourly,
+        BillingMode::Monthly,
+        BillingMode::Yearly1,
+        BillingMode::Yearly3,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BillingMode::Hourly => "hourly",
+            BillingMode::Monthly => "monthly",
+            BillingMode::Yearly1 => "1y",
+            BillingMode::Yearly3 => "3y",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "hourly" => Some(BillingMode::Hourly),
+            "monthly" => Some(BillingMode::Monthly),
+            "1y" | "yearly1" => Some(BillingMode::Yearly1),
+            "3y" | "yearly3" => Some(BillingMode::Yearly3),
+            _ => None,
+        }
+    }
+
+    // Flavor price column for this mode (whitelisted for ORDER BY)
+    pub fn price_column(&self) -> &'static str {
+        match self {
+            BillingMode::Hourly => "price_hourly",
+            BillingMode::Monthly => "price_monthly",
+            BillingMode::Yearly1 => "price_yearly_1",
+            BillingMode::Yearly3 => "price_yearly_3",
+        }
+    }
+
+    // Reserved for a year or more, with the price amortised per month
+    pub fn is_commitment(&self) -> bool {
+        self.months() > 1
+    }
+
+    // Commitment length in months (0 for pay-per-use)
+    pub fn months(&self) -> u32 {
+        match self {
+            BillingMode::Hourly => 0,
+            BillingMode::Monthly => 1,
+            BillingMode::Yearly1 => 12,
+            BillingMode::Yearly3 => 36,
+        }
+    }
+
+    // Fallback discount on the hourly rate when no catalog price is available,
+    // matching the crawler's reserved pricing
+    pub fn discount_factor(&self) -> f64 {
+        match self {
+            BillingMode::Hourly | BillingMode::Monthly => 1.0,
+            BillingMode::Yearly1 => 0.6,
+            BillingMode::Yearly3 => 0.4,
+        }
+    }
+}
+
+// Named runtime schedules for on-demand items
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageSchedule {
+    // 24x7
+    AlwaysOn,
+    // 24x5
+    Weekdays,
+    // 12x5
+    ExtendedHours,
+    // 8x5
+    BusinessHours,
+}
+
+impl UsageSchedule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageSchedule::AlwaysOn => "always_on",
+            UsageSchedule::Weekdays => "weekdays",
+            UsageSchedule::ExtendedHours => "extended_hours",
+            UsageSchedule::BusinessHours => "business_hours",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "always_on" => Some(UsageSchedule::AlwaysOn),
+            "weekdays" => Some(UsageSchedule::Weekdays),
+            "extended_hours" => Some(UsageSchedule::ExtendedHours),
+            "business_hours" => Some(UsageSchedule::BusinessHours),
+            _ => None,
+        }
+    }
+
+    // Average billed hours per month (52 weeks / 12 months for weekday schedules)
+    pub fn hours_per_month(&self) -> f64 {
+        let weeks_per_month = 52.0 / 12.0;
+        match self {
+            UsageSchedule::AlwaysOn => HOURS_PER_MONTH,
+            UsageSchedule::Weekdays => 24.0 * 5.0 * weeks_per_month,
+            UsageSchedule::ExtendedHours => 12.0 * 5.0 * weeks_per_month,
+            UsageSchedule::BusinessHours => 8.0 * 5.0 * weeks_per_month,
+        }
+    }
+}
+
+impl Flavor {
+    // Family prefix of the flavor name, e.g. "s6" for "s6.xlarge.2"
+    pub fn family(&self) -> &str {
+        self.name.split('.').next().unwrap_or(&self.name)
+    }
+
+    // Catalog price for one billing period of the given mode
+    pub fn price_for(&self, mode: BillingMode) -> f64 {
+        match mode {
+            BillingMode::Hourly => self.price_hourly,
+            BillingMode::Monthly => self.price_monthly,
+            BillingMode::Yearly1 => self.price_yearly_1,
+            BillingMode::Yearly3 => self.price_yearly_3,
+        }
+    }
+
+    // Monthly cost under the given mode; hourly assumes a full 720h month
+    pub fn monthly_cost(&self, mode: BillingMode) -> f64 {
+        match mode {
+            BillingMode::Hourly => self.price_hourly * HOURS_PER_MONTH,
+            _ => self.price_for(mode) / mode.months() as f64,
+        }
+    }
+}
+
+pub const FLAVOR_FAMILIES: [&str; 4] = ["s6", "s7", "c6", "m6"];
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct BestMatchQuery {
     pub vcpus: i32,
     pub ram_gb: f64,
+    pub family: Option<String>,
+    pub billing_mode: Option<BillingMode>,
+    // Maximum allowed excess over the requested vCPU/RAM, in percent
+    pub max_overshoot: Option<f64>,
+    pub limit: Option<u32>,
+}
+
+impl BestMatchQuery {
+    pub fn new(vcpus: i32, ram_gb: f64) -> Self {
+        Self {
+            vcpus,
+            ram_gb,
+            family: None,
+            billing_mode: None,
+            max_overshoot: None,
+            limit: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FlavorMatch {
+    #[serde(flatten)]
+    pub flavor: Flavor,
+    pub billing_mode: BillingMode,
+    pub price: f64,
+    pub excess_vcpus_pct: f64,
+    pub excess_ram_pct: f64,
+    // Average of excess vCPU and RAM percentages; lower is a tighter fit
+    pub waste_score: f64,
+}
+
+// Server-side cost projection for a quote, all amounts per month
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CostBreakdown {
+    pub compute_monthly: f64,
+    // OS licences, per vCPU-hour
+    pub licence_monthly: f64,
+    pub storage_monthly: f64,
+    // Backup vault storage
+    pub backup_monthly: f64,
+    // Elastic IPs and bandwidth
+    pub network_monthly: f64,
+    // Catalog SKUs (OBS, RDS, ...)
+    pub service_monthly: f64,
+    pub total_monthly: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemCost {
+    pub item_id: String,
+    pub hostname: Option<String>,
+    pub section_id: Option<String>,
+    pub billing_mode: BillingMode,
+    pub usage_hours_per_month: f64,
+    pub usage_schedule: Option<UsageSchedule>,
+    pub sku_category: Option<String>,
+    // Estimated backup vault size for the item's disk
+    pub backup_vault_gb: f64,
+    // List price breakdown, net of tax under an inclusive tax profile
+    #[serde(flatten)]
+    pub cost: CostBreakdown,
+    // Signed sum of item- and quote-level adjustments (discounts negative)
+    pub adjustment_monthly: f64,
+    // List + adjustments, excluding tax; gross = net + tax is what the customer pays
+    pub net_monthly: f64,
+    pub tax_monthly: f64,
+    pub gross_monthly: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategoryTotal {
+    // "compute", "licence", "storage", "backup", "network", or the SKU category
+    pub category: String,
+    pub total_monthly: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ModeTotal {
+    pub billing_mode: BillingMode,
+    pub item_count: u32,
+    #[serde(flatten)]
+    pub cost: CostBreakdown,
+    pub adjustment_monthly: f64,
+    pub net_monthly: f64,
+    pub tax_monthly: f64,
+    pub gross_monthly: f64,
+    // Full gross cost over the commitment term (one month for hourly/monthly)
+    pub commitment_total: f64,
+}
+
+// Subtotal for one section; items without a section are grouped last with no section_id
+#[derive(Debug, Serialize)]
+pub struct SectionTotal {
+    pub section_id: Option<String>,
+    pub name: String,
+    pub item_count: u32,
+    #[serde(flatten)]
+    pub cost: CostBreakdown,
+    pub adjustment_monthly: f64,
+    pub net_monthly: f64,
+    pub tax_monthly: f64,
+    pub gross_monthly: f64,
+}
+
+// An adjustment as applied to a quote, with the monthly amount it changed
+#[derive(Debug, Serialize)]
+pub struct AppliedAdjustment {
+    pub adjustment_id: String,
+    pub item_id: Option<String>,
+    pub kind: AdjustmentKind,
+    pub amount_type: AdjustmentType,
+    pub value: f64,
+    pub category: Option<String>,
+    pub description: Option<String>,
+    // Signed: negative for discounts
+    pub amount_monthly: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuoteTotals {
+    pub quote_id: String,
+    pub items: Vec<ItemCost>,
+    pub by_mode: Vec<ModeTotal>,
+    pub by_category: Vec<CategoryTotal>,
+    pub by_section: Vec<SectionTotal>,
+    pub backup_vault_gb: f64,
+    // List price breakdown, net of tax under an inclusive tax profile
+    #[serde(flatten)]
+    pub cost: CostBreakdown,
+    pub total_yearly: f64,
+    // Adjustments in the order they were applied
+    pub adjustments: Vec<AppliedAdjustment>,
+    pub adjustment_monthly: f64,
+    pub net_monthly: f64,
+    pub net_yearly: f64,
+    // None when the quote has no tax profile
+    pub tax_profile: Option<TaxProfile>,
+    pub tax_monthly: f64,
+    pub gross_monthly: f64,
+    pub gross_yearly: f64,
+}
+
+// Multi-year TCO and break-even analysis
+#[derive(Debug, Deserialize)]
+pub struct TcoQuery {
+    pub years: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TcoStrategy {
+    pub billing_mode: BillingMode,
+    // Cumulative cost at the end of each month, reserved terms paid upfront
+    pub cumulative: Vec<f64>,
+    pub total: f64,
+    // First month in which pay-as-you-go has cost at least as much (reserved modes only)
+    pub break_even_month: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemTco {
+    pub item_id: String,
+    pub hostname: Option<String>,
+    pub strategies: Vec<TcoStrategy>,
+    pub cheapest: BillingMode,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TcoReport {
+    pub quote_id: String,
+    pub years: u32,
+    pub months: u32,
+    pub strategies: Vec<TcoStrategy>,
+    pub cheapest: BillingMode,
+    pub items: Vec<ItemTco>,
+}
+
+// Bulk sizing
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorkloadRequirement {
+    pub name: String,
+    pub vcpus: i32,
+    pub ram_gb: f64,
+    pub disk_gb: Option<i32>,
+    // Disk type id, e.g. "ssd" or "gpssd"
+    pub disk_tier: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SizingRequest {
+    pub workloads: Vec<WorkloadRequirement>,
+    pub family: Option<String>,
+    pub billing_mode: Option<BillingMode>,
+    pub max_overshoot: Option<f64>,
+    // When set, a new quote with this name is created from the matched items
+    pub create_quote: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SizedWorkload {
+    pub name: String,
+    pub item: Option<CreateQuoteItem>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SizingTotals {
+    pub matched: u32,
+    pub unmatched: u32,
+    pub vcpus: i32,
+    pub ram_gb: f64,
+    pub disk_gb: i32,
+    pub compute_monthly: f64,
+    pub storage_monthly: f64,
+    pub total_monthly: f64,
+}
+
+impl std::ops::AddAssign for SizingTotals {
+    fn add_assign(&mut self, other: SizingTotals) {
+        self.matched += other.matched;
+        self.unmatched += other.unmatched;
+        self.vcpus += other.vcpus;
+        self.ram_gb += other.ram_gb;
+        self.disk_gb += other.disk_gb;
+        self.compute_monthly += other.compute_monthly;
+        self.storage_monthly += other.storage_monthly;
+        self.total_monthly += other.total_monthly;
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SizingResponse {
+    pub items: Vec<SizedWorkload>,
+    pub totals: SizingTotals,
+    pub quote: Option<Quote>,
+}
+
+// Utilization-based rightsizing
+#[derive(Debug, Deserialize)]
+pub struct ItemUtilization {
+    // Matched against item hostname, then code_number
+    pub key: String,
+    pub cpu_peak_pct: f64,
+    pub ram_peak_pct: f64,
+}
+
+impl ItemUtilization {
+    pub fn is_valid(&self) -> bool {
+        (0.0..=100.0).contains(&self.cpu_peak_pct) && (0.0..=100.0).contains(&self.ram_peak_pct)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RightsizeRequest {
+    pub utilization: Vec<ItemUtilization>,
+    // Extra capacity kept above observed peaks, in percent (default 20)
+    pub headroom_pct: Option<f64>,
+    // Restrict suggestions to this family; defaults to each item's current family
+    pub family: Option<String>,
+    pub billing_mode: Option<BillingMode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RightsizeSuggestion {
+    pub item_id: String,
+    pub key: String,
+    pub current_flavor: Option<String>,
+    pub required_vcpus: i32,
+    pub required_ram_gb: f64,
+    pub suggested: Option<FlavorMatch>,
+    pub current_monthly: f64,
+    pub suggested_monthly: f64,
+    pub savings_monthly: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RightsizeResponse {
+    pub quote_id: String,
+    pub billing_mode: BillingMode,
+    pub suggestions: Vec<RightsizeSuggestion>,
+    pub unmatched_keys: Vec<String>,
+    pub current_monthly: f64,
+    pub suggested_monthly: f64,
+    pub savings_monthly: f64,
+}
+
+// Budget-constrained optimizer
+#[derive(Debug, Deserialize)]
+pub struct OptimizeRequest {
+    pub workloads: Vec<WorkloadRequirement>,
+    pub monthly_budget: f64,
+    pub family: Option<String>,
+    // Billing modes the optimizer may choose from; defaults to all
+    pub billing_modes: Option<Vec<BillingMode>>,
+    // 1y/3y amortise an upfront commitment per month and would always win on
+    // monthly cost, so they are only considered when allowed
+    pub allow_commitments: Option<bool>,
+}
+
+impl OptimizeRequest {
+    pub fn allowed_billing_modes(&self) -> Vec<BillingMode> {
+        let modes = match &self.billing_modes {
+            Some(modes) if !modes.is_empty() => modes.clone(),
+            _ => BillingMode::ALL.to_vec(),
+        };
+        let allow_commitments = self.allow_commitments.unwrap_or(false);
+        modes.into_iter().filter(|mode| allow_commitments || !mode.is_commitment()).collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct OptimizedWorkload {
+    pub name: String,
+    pub billing_mode: BillingMode,
+    pub compute_monthly: f64,
+    pub storage_monthly: f64,
+    pub total_monthly: f64,
+    pub item: CreateQuoteItem,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnfitWorkload {
+    pub name: String,
+    pub reason: String,
+    // Cheapest monthly cost found, if the catalog could satisfy the requirement at all
+    pub min_monthly: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OptimizeResponse {
+    pub monthly_budget: f64,
+    pub total_monthly: f64,
+    pub remaining_budget: f64,
+    // Cheapest cost of every workload the catalog can size; within_budget when
+    // it fits, whether or not some workloads can't be sized at all
+    pub required_monthly: f64,
+    pub within_budget: bool,
+    pub selections: Vec<OptimizedWorkload>,
+    pub unfit: Vec<UnfitWorkload>,
 }
 
 // Pagination
@@ -113,6 +1484,7 @@ pub struct PaginationQuery {
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
     pub search: Option<String>,
+    pub customer_id: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -149,3 +1521,122 @@ impl ApiError {
         }
     }
 }
+
+// Parametric stack blueprints, loaded from BLUEPRINTS_DIR at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Blueprint {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<BlueprintParameter>,
+    pub components: Vec<BlueprintComponent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlueprintParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub default: f64,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+}
+
+// A literal number or the name of a blueprint parameter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BlueprintValue {
+    Number(f64),
+    Param(String),
+}
+
+// One group of identical instances, sized with the best-matching flavor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlueprintComponent {
+    pub name: String,
+    // Number of instances, 1 when unset; 0 leaves the component out
+    #[serde(default)]
+    pub count: Option<BlueprintValue>,
+    pub vcpus: BlueprintValue,
+    pub ram_gb: BlueprintValue,
+    #[serde(default)]
+    pub family: Option<String>,
+    // Disk type id, e.g. "ssd"; requires disk_gb
+    #[serde(default)]
+    pub disk_type: Option<String>,
+    #[serde(default)]
+    pub disk_gb: Option<BlueprintValue>,
+    // "{n}" is replaced by the instance number; defaults to "<name>-{n}"
+    #[serde(default)]
+    pub hostname: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpandBlueprint {
+    // Parameter values; omitted parameters use their defaults
+    #[serde(default)]
+    pub parameters: BTreeMap<String, f64>,
+    pub billing_mode: Option<BillingMode>,
+    pub max_overshoot: Option<f64>,
+    // When set, the expanded items are added to this quote
+    pub quote_id: Option<String>,
+    pub section_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlueprintExpansion {
+    pub blueprint_id: String,
+    // Parameter values used, defaults included
+    pub parameters: BTreeMap<String, f64>,
+    pub items: Vec<SizedWorkload>,
+    pub totals: SizingTotals,
+    // Items added to the quote when quote_id was given
+    pub created: Option<Vec<QuoteItem>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{assert_close, tax_profile};
+
+    #[test]
+    fn inclusive_tax_is_split_out_of_the_amount() {
+        let vat = tax_profile(20.0, true);
+        let net = vat.net_of(120.0);
+        assert_close(net, 100.0);
+        assert_close(vat.tax_on(net), 20.0);
+    }
+
+    #[test]
+    fn exclusive_tax_is_added_on_top() {
+        let vat = tax_profile(20.0, false);
+        let net = vat.net_of(100.0);
+        assert_close(net, 100.0);
+        assert_close(vat.tax_on(net), 20.0);
+    }
+
+    #[test]
+    fn usage_schedules_bill_average_weekday_hours() {
+        assert_close(UsageSchedule::AlwaysOn.hours_per_month(), HOURS_PER_MONTH);
+        assert_close(UsageSchedule::Weekdays.hours_per_month(), 520.0);
+        assert_close(UsageSchedule::ExtendedHours.hours_per_month(), 260.0);
+        assert_close(UsageSchedule::BusinessHours.hours_per_month(), 520.0 / 3.0);
+    }
+
+    #[test]
+    fn backup_vault_grows_with_retention_and_change_rate() {
+        assert_close(BackupPolicy::vault_size_gb(100.0, 7, 10.0), 170.0);
+        assert_close(BackupPolicy::vault_size_gb(100.0, 0, 10.0), 100.0);
+        assert_close(BackupPolicy::vault_size_gb(100.0, -5, 10.0), 100.0);
+        assert_close(BackupPolicy::vault_size_gb(100.0, 7, -10.0), 100.0);
+    }
+}
@@ -0,0 +1,240 @@
+use rusqlite::Result;
+
+use crate::db::Database;
+use crate::models::*;
+
+// Cheapest catalog option for a single workload, priced per month
+struct Candidate {
+    flavor: Flavor,
+    billing_mode: BillingMode,
+    disk: Option<(DiskType, i32)>,
+    compute_monthly: f64,
+    storage_monthly: f64,
+}
+
+impl Candidate {
+    fn total_monthly(&self) -> f64 {
+        self.compute_monthly + self.storage_monthly
+    }
+}
+
+// Choose flavors, disk types and billing modes that satisfy every workload at
+// minimum monthly cost. Workloads are independent, so the per-workload minimum
+// is the global minimum. When that exceeds the budget, the cheapest workloads
+// are kept first, which maximizes how many requirements fit.
+pub fn optimize(db: &Database, req: &OptimizeRequest) -> Result<OptimizeResponse> {
+    let modes = req.allowed_billing_modes();
+    let disk_types = db.get_disk_types()?;
+
+    let mut candidates = Vec::new();
+    let mut unfit = Vec::new();
+
+    for workload in &req.workloads {
+        let mut best: Option<Candidate> = None;
+        for &mode in &modes {
+            let query = BestMatchQuery {
+                family: req.family.clone(),
+                billing_mode: Some(mode),
+                limit: Some(1),
+                ..BestMatchQuery::new(workload.vcpus, workload.ram_gb)
+            };
+            let Some(m) = db.find_best_match(&query)?.into_iter().next() else {
+                continue;
+            };
+            let compute_monthly = m.flavor.monthly_cost(mode);
+            if best.as_ref().is_none_or(|b| compute_monthly < b.compute_monthly) {
+                best = Some(Candidate {
+                    flavor: m.flavor,
+                    billing_mode: mode,
+                    disk: None,
+                    compute_monthly,
+                    storage_monthly: 0.0,
+                });
+            }
+        }
+
+        let Some(mut candidate) = best else {
+            unfit.push(UnfitWorkload {
+                name: workload.name.clone(),
+                reason: format!(
+                    "No flavor with at least {} vCPU and {} GB RAM",
+                    workload.vcpus, workload.ram_gb
+                ),
+                min_monthly: None,
+            });
+            continue;
+        };
+
+        // A requested tier is kept as-is; otherwise the cheapest disk type is used
+        if let Some(size) = workload.disk_gb.filter(|gb| *gb > 0) {
+            let disk = match &workload.disk_tier {
+                Some(tier) => disk_types.iter().find(|d| &d.id == tier),
+                None => disk_types.first(),
+            };
+            let Some(disk) = disk else {
+                unfit.push(UnfitWorkload {
+                    name: workload.name.clone(),
+                    reason: match &workload.disk_tier {
+                        Some(tier) => format!("Unknown disk tier '{}'", tier),
+                        None => "No disk types available".to_string(),
+                    },
+                    min_monthly: None,
+                });
+                continue;
+            };
+            candidate.storage_monthly = disk.price_per_gb * size as f64;
+            candidate.disk = Some((disk.clone(), size));
+        }
+
+        candidates.push((workload, candidate));
+    }
+
+    candidates.sort_by(|a, b| a.1.total_monthly().total_cmp(&b.1.total_monthly()));
+    let required_monthly: f64 = candidates.iter().map(|(_, c)| c.total_monthly()).sum();
+
+    let mut total_monthly = 0.0;
+    let mut selections = Vec::new();
+    for (workload, c) in candidates {
+        let cost = c.total_monthly();
+        if total_monthly + cost > req.monthly_budget {
+            unfit.push(UnfitWorkload {
+                name: workload.name.clone(),
+                reason: "Exceeds remaining budget".to_string(),
+                min_monthly: Some(cost),
+            });
+            continue;
+        }
+        total_monthly += cost;
+
+        selections.push(OptimizedWorkload {
+            name: workload.name.clone(),
+            billing_mode: c.billing_mode,
+            compute_monthly: c.compute_monthly,
+            storage_monthly: c.storage_monthly,
+            total_monthly: cost,
+            item: CreateQuoteItem {
+                flavor_id: Some(c.flavor.id.clone()),
+                flavor_name: Some(c.flavor.name.clone()),
+                vcpus: Some(c.flavor.vcpus),
+                ram_gb: Some(c.flavor.ram_gb),
+                flavor_price: Some(c.flavor.price_hourly),
+                disk_type_id: c.disk.as_ref().map(|(d, _)| d.id.clone()),
+                disk_type_name: c.disk.as_ref().map(|(d, _)| d.name.clone()),
+                disk_size_gb: c.disk.as_ref().map(|(_, size)| *size),
+                disk_price: c.disk.as_ref().map(|_| c.storage_monthly),
+                hostname: Some(workload.name.clone()),
+                billing_mode: Some(c.billing_mode),
+                ..Default::default()
+            },
+        });
+    }
+
+    Ok(OptimizeResponse {
+        monthly_budget: req.monthly_budget,
+        total_monthly,
+        remaining_budget: req.monthly_budget - total_monthly,
+        required_monthly,
+        within_budget: required_monthly <= req.monthly_budget,
+        selections,
+        unfit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{assert_close, database, disk_type, flavor};
+
+    fn catalog() -> Database {
+        let db = database();
+        for f in [flavor("s6.small", 1, 2.0, 0.01), flavor("s6.medium", 2, 4.0, 0.02), flavor("s6.large", 4, 8.0, 0.04)] {
+            db.insert_flavor(&f).unwrap();
+        }
+        for d in [disk_type("sata", 0.05), disk_type("ssd", 0.1)] {
+            db.insert_disk_type(&d).unwrap();
+        }
+        db
+    }
+
+    fn workload(name: &str, vcpus: i32, ram_gb: f64, disk_gb: Option<i32>, disk_tier: Option<&str>) -> WorkloadRequirement {
+        WorkloadRequirement {
+            name: name.to_string(),
+            vcpus,
+            ram_gb,
+            disk_gb,
+            disk_tier: disk_tier.map(str::to_string),
+        }
+    }
+
+    fn request(workloads: Vec<WorkloadRequirement>, monthly_budget: f64) -> OptimizeRequest {
+        OptimizeRequest {
+            workloads,
+            monthly_budget,
+            family: None,
+            billing_modes: None,
+            allow_commitments: None,
+        }
+    }
+
+    #[test]
+    fn cheapest_workloads_are_kept_within_the_budget() {
+        let req = request(
+            vec![
+                workload("db", 4, 8.0, Some(100), Some("ssd")),
+                workload("web", 1, 2.0, Some(100), None),
+                workload("huge", 64, 256.0, None, None),
+                workload("app", 2, 4.0, None, None),
+            ],
+            30.0,
+        );
+
+        let result = optimize(&catalog(), &req).unwrap();
+
+        // web: 7.20 compute + 5.00 on the cheapest disk; app: 14.40; db: 28.80 + 10.00 on ssd
+        let selected: Vec<(&str, f64)> = result.selections.iter().map(|s| (s.name.as_str(), s.total_monthly)).collect();
+        assert_eq!(selected.len(), 2);
+        assert_eq!((selected[0].0, selected[1].0), ("web", "app"));
+        assert_close(selected[0].1, 12.2);
+        assert_eq!(result.selections[0].item.disk_type_id.as_deref(), Some("sata"));
+        assert_close(result.total_monthly, 26.6);
+        assert_close(result.remaining_budget, 3.4);
+        assert_close(result.required_monthly, 65.4);
+        assert!(!result.within_budget);
+
+        let unfit: Vec<(&str, &str)> = result.unfit.iter().map(|u| (u.name.as_str(), u.reason.as_str())).collect();
+        assert_eq!(
+            unfit,
+            vec![
+                ("huge", "No flavor with at least 64 vCPU and 256 GB RAM"),
+                ("db", "Exceeds remaining budget"),
+            ]
+        );
+        assert_eq!(result.unfit[0].min_monthly, None);
+        assert_close(result.unfit[1].min_monthly.unwrap(), 38.8);
+    }
+
+    #[test]
+    fn commitments_are_only_chosen_when_allowed() {
+        let on_demand = request(vec![workload("app", 2, 4.0, None, None)], 100.0);
+        let committed = OptimizeRequest { allow_commitments: Some(true), ..request(on_demand.workloads.clone(), 100.0) };
+
+        let result = optimize(&catalog(), &on_demand).unwrap();
+        assert!(!result.selections[0].billing_mode.is_commitment());
+        assert_close(result.total_monthly, 14.4);
+
+        let result = optimize(&catalog(), &committed).unwrap();
+        assert_eq!(result.selections[0].billing_mode, BillingMode::Yearly3);
+        assert_close(result.total_monthly, 14.4 * BillingMode::Yearly3.discount_factor());
+        assert!(result.within_budget);
+    }
+
+    #[test]
+    fn unknown_disk_tiers_are_reported() {
+        let req = request(vec![workload("web", 1, 2.0, Some(50), Some("nvme"))], 100.0);
+
+        let result = optimize(&catalog(), &req).unwrap();
+
+        assert!(result.selections.is_empty());
+        assert_eq!(result.unfit[0].reason, "Unknown disk tier 'nvme'");
+    }
+}
@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+
+use crate::adjustments::apply_adjustments;
+use crate::models::*;
+
+// Hours billed for an hourly item: explicit hours, then the named schedule,
+// then a full month. Other modes always cover the full month.
+pub fn usage_hours(item: &QuoteItem) -> f64 {
+    match item.billing_mode {
+        BillingMode::Hourly => item
+            .usage_hours_per_month
+            .or_else(|| item.usage_schedule.map(|s| s.hours_per_month()))
+            .unwrap_or(HOURS_PER_MONTH)
+            .clamp(0.0, HOURS_PER_MONTH),
+        _ => HOURS_PER_MONTH,
+    }
+}
+
+impl CostBreakdown {
+    fn new(
+        compute_monthly: f64,
+        licence_monthly: f64,
+        storage_monthly: f64,
+        backup_monthly: f64,
+        network_monthly: f64,
+        service_monthly: f64,
+    ) -> Self {
+        Self {
+            compute_monthly,
+            licence_monthly,
+            storage_monthly,
+            backup_monthly,
+            network_monthly,
+            service_monthly,
+            total_monthly: compute_monthly
+                + licence_monthly
+                + storage_monthly
+                + backup_monthly
+                + network_monthly
+                + service_monthly,
+        }
+    }
+
+    fn add(&mut self, other: &CostBreakdown) {
+        self.compute_monthly += other.compute_monthly;
+        self.licence_monthly += other.licence_monthly;
+        self.storage_monthly += other.storage_monthly;
+        self.backup_monthly += other.backup_monthly;
+        self.network_monthly += other.network_monthly;
+        self.service_monthly += other.service_monthly;
+        self.total_monthly += other.total_monthly;
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.compute_monthly *= factor;
+        self.licence_monthly *= factor;
+        self.storage_monthly *= factor;
+        self.backup_monthly *= factor;
+        self.network_monthly *= factor;
+        self.service_monthly *= factor;
+        self.total_monthly *= factor;
+    }
+
+    fn sum<'a>(costs: impl IntoIterator<Item = &'a CostBreakdown>) -> Self {
+        let mut total = CostBreakdown::default();
+        for cost in costs {
+            total.add(cost);
+        }
+        total
+    }
+}
+
+// Monthly cost of the item's catalog SKU, by pricing unit
+fn sku_cost(item: &QuoteItem, hours: f64) -> f64 {
+    let (Some(unit), Some(price)) = (item.sku_unit, item.sku_price) else {
+        return 0.0;
+    };
+    let quantity = item.sku_quantity.unwrap_or(1.0);
+
+    match unit {
+        PricingUnit::GbMonth | PricingUnit::Request => price * quantity,
+        PricingUnit::Hour => price * quantity * hours * item.billing_mode.discount_factor(),
+    }
+}
+
+// OS licence cost: per vCPU-hour for the hours the server runs. Licences are
+// not discounted by reserved billing modes.
+fn licence_cost(item: &QuoteItem, hours: f64) -> f64 {
+    item.os_licence_price.unwrap_or(0.0) * item.vcpus.unwrap_or(0) as f64 * hours
+}
+
+// Estimated backup vault size for the item's disk, 0 without a backup policy
+fn backup_vault_gb(item: &QuoteItem) -> f64 {
+    if item.backup_policy_id.is_none() {
+        return 0.0;
+    }
+    BackupPolicy::vault_size_gb(
+        item.disk_size_gb.unwrap_or(0) as f64,
+        item.backup_retention_days.unwrap_or(0),
+        item.backup_change_rate_pct.unwrap_or(0.0),
+    )
+}
+
+// Monthly cost of a single item under its own billing mode. The stored
+// flavor_price is the hourly rate captured when the item was added.
+pub fn item_cost(item: &QuoteItem) -> ItemCost {
+    let hours = usage_hours(item);
+    let hourly = item.flavor_price.unwrap_or(0.0);
+    let compute_monthly = hourly * hours * item.billing_mode.discount_factor();
+    // Disks stay allocated when an on-demand server is stopped, so storage is billed monthly
+    let storage_monthly = item.disk_price.unwrap_or(0.0);
+    let network_monthly = item.bandwidth_price.unwrap_or(0.0);
+    let vault_gb = backup_vault_gb(item);
+    let backup_monthly = vault_gb * item.backup_price_per_gb.unwrap_or(0.0);
+
+    let cost = CostBreakdown::new(
+        compute_monthly,
+        licence_cost(item, hours),
+        storage_monthly,
+        backup_monthly,
+        network_monthly,
+        sku_cost(item, hours),
+    );
+
+    ItemCost {
+        item_id: item.id.clone(),
+        hostname: item.hostname.clone(),
+        section_id: item.section_id.clone(),
+        billing_mode: item.billing_mode,
+        usage_hours_per_month: hours,
+        usage_schedule: item.usage_schedule,
+        sku_category: item.sku_category.clone(),
+        backup_vault_gb: vault_gb,
+        adjustment_monthly: 0.0,
+        net_monthly: cost.total_monthly,
+        tax_monthly: 0.0,
+        gross_monthly: cost.total_monthly,
+        cost,
+    }
+}
+
+// Monthly totals per cost category, SKU categories listed individually
+fn category_totals(costs: &[ItemCost]) -> Vec<CategoryTotal> {
+    let total = CostBreakdown::sum(costs.iter().map(|c| &c.cost));
+    let mut categories = vec![
+        CategoryTotal { category: "compute".to_string(), total_monthly: total.compute_monthly },
+        CategoryTotal { category: "licence".to_string(), total_monthly: total.licence_monthly },
+        CategoryTotal { category: "storage".to_string(), total_monthly: total.storage_monthly },
+        CategoryTotal { category: "backup".to_string(), total_monthly: total.backup_monthly },
+        CategoryTotal { category: "network".to_string(), total_monthly: total.network_monthly },
+    ];
+
+    for cost in costs.iter().filter(|c| c.cost.service_monthly != 0.0) {
+        let name = cost.sku_category.clone().unwrap_or_else(|| "other".to_string());
+        match categories.iter_mut().find(|c| c.category == name) {
+            Some(category) => category.total_monthly += cost.cost.service_monthly,
+            None => categories.push(CategoryTotal { category: name, total_monthly: cost.cost.service_monthly }),
+        }
+    }
+
+    categories
+}
+
+// Inclusive catalog prices already contain the tax: take it out of the list
+// prices before adjustments, so list + adjustments = net under every profile
+fn exclude_tax(costs: &mut [ItemCost], tax: Option<&TaxProfile>) {
+    let Some(profile) = tax.filter(|profile| profile.inclusive) else {
+        return;
+    };
+    for cost in costs.iter_mut() {
+        cost.cost.scale(profile.net_of(1.0));
+        cost.net_monthly = cost.cost.total_monthly;
+        cost.gross_monthly = cost.cost.total_monthly;
+    }
+}
+
+// Tax on each item's net amount under the quote's profile
+fn apply_tax(costs: &mut [ItemCost], tax: Option<&TaxProfile>) {
+    for cost in costs.iter_mut() {
+        cost.tax_monthly = tax.map_or(0.0, |profile| profile.tax_on(cost.net_monthly));
+        cost.gross_monthly = cost.net_monthly + cost.tax_monthly;
+    }
+}
+
+// Subtotals per section in section order, then items without a known section
+fn section_totals(costs: &[ItemCost], sections: &[QuoteSection]) -> Vec<SectionTotal> {
+    let subtotal = |section_id: Option<String>, name: String, in_section: Vec<&ItemCost>| SectionTotal {
+        section_id,
+        name,
+        item_count: in_section.len() as u32,
+        cost: CostBreakdown::sum(in_section.iter().map(|c| &c.cost)),
+        adjustment_monthly: in_section.iter().map(|c| c.adjustment_monthly).sum(),
+        net_monthly: in_section.iter().map(|c| c.net_monthly).sum(),
+        tax_monthly: in_section.iter().map(|c| c.tax_monthly).sum(),
+        gross_monthly: in_section.iter().map(|c| c.gross_monthly).sum(),
+    };
+
+    let mut totals: Vec<SectionTotal> = sections
+        .iter()
+        .map(|section| {
+            let in_section = costs.iter().filter(|c| c.section_id.as_ref() == Some(&section.id)).collect();
+            subtotal(Some(section.id.clone()), section.name.clone(), in_section)
+        })
+        .collect();
+
+    let unsectioned: Vec<&ItemCost> = costs
+        .iter()
+        .filter(|c| c.section_id.as_ref().is_none_or(|id| sections.iter().all(|s| &s.id != id)))
+        .collect();
+    if !unsectioned.is_empty() {
+        totals.push(subtotal(None, "Unsectioned".to_string(), unsectioned));
+    }
+
+    totals
+}
+
+// Combine items with mixed billing modes into one monthly projection, with
+// list price, adjustments, net, tax and gross reported separately
+pub fn quote_totals(quote_id: &str, pricing: &QuotePricing) -> QuoteTotals {
+    let tax = pricing.tax.as_ref();
+    let mut costs: Vec<ItemCost> = pricing.items.iter().map(item_cost).collect();
+    exclude_tax(&mut costs, tax);
+    let applied = apply_adjustments(&mut costs, &pricing.adjustments);
+    apply_tax(&mut costs, tax);
+
+    let by_mode: Vec<ModeTotal> = BillingMode::ALL
+        .iter()
+        .filter_map(|&mode| {
+            let in_mode: Vec<&ItemCost> = costs.iter().filter(|c| c.billing_mode == mode).collect();
+            if in_mode.is_empty() {
+                return None;
+            }
+            let gross_monthly: f64 = in_mode.iter().map(|c| c.gross_monthly).sum();
+            Some(ModeTotal {
+                billing_mode: mode,
+                item_count: in_mode.len() as u32,
+                cost: CostBreakdown::sum(in_mode.iter().map(|c| &c.cost)),
+                adjustment_monthly: in_mode.iter().map(|c| c.adjustment_monthly).sum(),
+                net_monthly: in_mode.iter().map(|c| c.net_monthly).sum(),
+                tax_monthly: in_mode.iter().map(|c| c.tax_monthly).sum(),
+                gross_monthly,
+                commitment_total: gross_monthly * mode.months().max(1) as f64,
+            })
+        })
+        .collect();
+
+    let cost = CostBreakdown::sum(costs.iter().map(|c| &c.cost));
+    let adjustment_monthly: f64 = costs.iter().map(|c| c.adjustment_monthly).sum();
+    let net_monthly: f64 = costs.iter().map(|c| c.net_monthly).sum();
+    let tax_monthly: f64 = costs.iter().map(|c| c.tax_monthly).sum();
+    let gross_monthly = net_monthly + tax_monthly;
+
+    QuoteTotals {
+        quote_id: quote_id.to_string(),
+        by_mode,
+        by_category: category_totals(&costs),
+        by_section: section_totals(&costs, &pricing.sections),
+        backup_vault_gb: costs.iter().map(|c| c.backup_vault_gb).sum(),
+        items: costs,
+        total_yearly: cost.total_monthly * 12.0,
+        cost,
+        adjustments: applied,
+        adjustment_monthly,
+        net_monthly,
+        net_yearly: net_monthly * 12.0,
+        tax_profile: tax.cloned(),
+        tax_monthly,
+        gross_monthly,
+        gross_yearly: gross_monthly * 12.0,
+    }
+}
+
+// Strategies compared by the TCO report
+const TCO_STRATEGIES: [BillingMode; 3] = [BillingMode::Hourly, BillingMode::Yearly1, BillingMode::Yearly3];
+
+// Catalog flavor for the item, or one derived from its stored hourly price
+// using the crawler's reserved pricing formula
+fn item_flavor_prices(item: &QuoteItem, catalog: &HashMap<String, Flavor>) -> (f64, f64, f64) {
+    match item.flavor_id.as_ref().and_then(|id| catalog.get(id)) {
+        Some(f) => (f.price_hourly, f.price_yearly_1, f.price_yearly_3),
+        None => {
+            let hourly = item.flavor_price.unwrap_or(0.0);
+            let monthly = hourly * HOURS_PER_MONTH;
+            (
+                hourly,
+                monthly * 12.0 * BillingMode::Yearly1.discount_factor(),
+                monthly * 36.0 * BillingMode::Yearly3.discount_factor(),
+            )
+        }
+    }
+}
+
+// Month-by-month cumulative cost of one item under a strategy
+fn cumulative_series(item: &QuoteItem, catalog: &HashMap<String, Flavor>, mode: BillingMode, months: u32) -> Vec<f64> {
+    let (hourly, yearly_1, yearly_3) = item_flavor_prices(item, catalog);
+    // Everything except compute (licences, storage, backups, bandwidth, catalog SKUs)
+    // costs the same under every compute strategy
+    let cost = item_cost(item).cost;
+    let storage = cost.total_monthly - cost.compute_monthly;
+
+    let mut total = 0.0;
+    (0..months)
+        .map(|m| {
+            total += storage;
+            total += match mode {
+                BillingMode::Yearly1 if m % 12 == 0 => yearly_1,
+                BillingMode::Yearly3 if m % 36 == 0 => yearly_3,
+                BillingMode::Yearly1 | BillingMode::Yearly3 => 0.0,
+                _ => hourly * usage_hours(item),
+            };
+            total
+        })
+        .collect()
+}
+
+fn build_strategies(series: Vec<(BillingMode, Vec<f64>)>) -> (Vec<TcoStrategy>, BillingMode) {
+    let payg = series
+        .iter()
+        .find(|(mode, _)| *mode == BillingMode::Hourly)
+        .map(|(_, s)| s.clone())
+        .unwrap_or_default();
+
+    let strategies: Vec<TcoStrategy> = series
+        .into_iter()
+        .map(|(mode, cumulative)| {
+            let break_even_month = match mode {
+                BillingMode::Hourly | BillingMode::Monthly => None,
+                _ => cumulative
+                    .iter()
+                    .zip(&payg)
+                    .position(|(reserved, on_demand)| on_demand >= reserved)
+                    .map(|m| m as u32 + 1),
+            };
+            TcoStrategy {
+                billing_mode: mode,
+                total: cumulative.last().copied().unwrap_or(0.0),
+                cumulative,
+                break_even_month,
+            }
+        })
+        .collect();
+
+    let cheapest = strategies
+        .iter()
+        .min_by(|a, b| a.total.total_cmp(&b.total))
+        .map(|s| s.billing_mode)
+        .unwrap_or(BillingMode::Hourly);
+
+    (strategies, cheapest)
+}
+
+// Compare pay-as-you-go against 1- and 3-year reservations over a horizon
+pub fn tco_report(quote_id: &str, items: &[QuoteItem], catalog: &HashMap<String, Flavor>, years: u32) -> TcoReport {
+    let months = years * 12;
+    let mut quote_series: Vec<(BillingMode, Vec<f64>)> =
+        TCO_STRATEGIES.iter().map(|&mode| (mode, vec![0.0; months as usize])).collect();
+
+    let item_reports = items
+        .iter()
+        .map(|item| {
+            let series: Vec<(BillingMode, Vec<f64>)> = TCO_STRATEGIES
+                .iter()
+                .map(|&mode| (mode, cumulative_series(item, catalog, mode, months)))
+                .collect();
+
+            for ((_, quote_cumulative), (_, item_cumulative)) in quote_series.iter_mut().zip(&series) {
+                for (q, i) in quote_cumulative.iter_mut().zip(item_cumulative) {
+                    *q += i;
+                }
+            }
+
+            let (strategies, cheapest) = build_strategies(series);
+            ItemTco {
+                item_id: item.id.clone(),
+                hostname: item.hostname.clone(),
+                strategies,
+                cheapest,
+            }
+        })
+        .collect();
+
+    let (strategies, cheapest) = build_strategies(quote_series);
+    TcoReport {
+        quote_id: quote_id.to_string(),
+        years,
+        months,
+        strategies,
+        cheapest,
+        items: item_reports,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{assert_close, quote_item, tax_profile};
+
+    // Item whose only cost is a monthly disk price
+    fn disk_item(id: &str, disk_price: f64) -> QuoteItem {
+        quote_item(id, serde_json::json!({ "disk_price": disk_price }))
+    }
+
+    fn discount(percent: f64) -> Adjustment {
+        Adjustment {
+            id: "d".to_string(),
+            quote_id: "q".to_string(),
+            item_id: None,
+            kind: AdjustmentKind::Discount,
+            amount_type: AdjustmentType::Percent,
+            value: percent,
+            category: None,
+            description: None,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn exclusive_tax_is_added_to_the_adjusted_amount() {
+        let pricing = QuotePricing {
+            items: vec![disk_item("a", 100.0)],
+            adjustments: vec![discount(10.0)],
+            sections: vec![],
+            tax: Some(tax_profile(20.0, false)),
+        };
+
+        let totals = quote_totals("q", &pricing);
+
+        assert_close(totals.cost.total_monthly, 100.0);
+        assert_close(totals.adjustment_monthly, -10.0);
+        assert_close(totals.net_monthly, 90.0);
+        assert_close(totals.tax_monthly, 18.0);
+        assert_close(totals.gross_monthly, 108.0);
+        assert_close(totals.gross_yearly, 1296.0);
+    }
+
+    #[test]
+    fn inclusive_tax_is_taken_out_of_list_prices() {
+        let pricing = QuotePricing {
+            items: vec![disk_item("a", 120.0)],
+            adjustments: vec![discount(50.0)],
+            sections: vec![],
+            tax: Some(tax_profile(20.0, true)),
+        };
+
+        let totals = quote_totals("q", &pricing);
+
+        assert_close(totals.cost.total_monthly, 100.0);
+        assert_close(totals.net_monthly, totals.cost.total_monthly + totals.adjustment_monthly);
+        assert_close(totals.net_monthly, 50.0);
+        assert_close(totals.tax_monthly, 10.0);
+        assert_close(totals.gross_monthly, 60.0);
+    }
+
+    #[test]
+    fn totals_without_tax_profile_have_no_tax() {
+        let pricing = QuotePricing {
+            items: vec![disk_item("a", 30.0), disk_item("b", 70.0)],
+            adjustments: vec![],
+            sections: vec![],
+            tax: None,
+        };
+
+        let totals = quote_totals("q", &pricing);
+
+        assert_close(totals.tax_monthly, 0.0);
+        assert_close(totals.gross_monthly, 100.0);
+        assert_eq!(totals.by_mode.len(), 1);
+        assert_eq!(totals.by_mode[0].item_count, 2);
+    }
+
+    #[test]
+    fn sections_are_totalled_in_order_with_unsectioned_items_last() {
+        let section = |id: &str, name: &str| QuoteSection {
+            id: id.to_string(),
+            quote_id: "q".to_string(),
+            name: name.to_string(),
+            position: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        };
+        let sections = vec![section("web", "Web"), section("db", "Database")];
+        let in_section = |id: &str, disk_price: f64, section_id: Option<&str>| {
+            quote_item(id, serde_json::json!({ "disk_price": disk_price, "section_id": section_id }))
+        };
+        let costs: Vec<ItemCost> = [
+            in_section("a", 10.0, Some("db")),
+            in_section("b", 20.0, None),
+            in_section("c", 30.0, Some("gone")),
+            in_section("d", 40.0, Some("db")),
+        ]
+        .iter()
+        .map(item_cost)
+        .collect();
+
+        let totals = section_totals(&costs, &sections);
+
+        let summary: Vec<(Option<&str>, u32, f64)> = totals
+            .iter()
+            .map(|t| (t.section_id.as_deref(), t.item_count, t.net_monthly))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(Some("web"), 0, 0.0), (Some("db"), 2, 50.0), (None, 2, 50.0)]
+        );
+        assert_eq!(totals[2].name, "Unsectioned");
+    }
+
+    #[test]
+    fn reservations_break_even_when_pay_as_you_go_catches_up() {
+        // 100/month on demand against a 1-year term at 1000 and a 3-year term at 1800, both upfront
+        let payg: Vec<f64> = (1..=24).map(|m| m as f64 * 100.0).collect();
+        let yearly_1: Vec<f64> = (0..24).map(|m| if m < 12 { 1000.0 } else { 2000.0 }).collect();
+        let yearly_3 = vec![1800.0; 24];
+
+        let (strategies, cheapest) = build_strategies(vec![
+            (BillingMode::Hourly, payg),
+            (BillingMode::Yearly1, yearly_1),
+            (BillingMode::Yearly3, yearly_3),
+        ]);
+
+        let summary: Vec<(BillingMode, Option<u32>)> =
+            strategies.iter().map(|s| (s.billing_mode, s.break_even_month)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (BillingMode::Hourly, None),
+                (BillingMode::Yearly1, Some(10)),
+                (BillingMode::Yearly3, Some(18)),
+            ]
+        );
+        assert_close(strategies[0].total, 2400.0);
+        assert_close(strategies[1].total, 2000.0);
+        assert_eq!(cheapest, BillingMode::Yearly3);
+    }
+
+    #[test]
+    fn reservations_that_never_pay_off_have_no_break_even() {
+        let (strategies, cheapest) = build_strategies(vec![
+            (BillingMode::Hourly, vec![10.0, 20.0, 30.0]),
+            (BillingMode::Yearly1, vec![500.0, 500.0, 500.0]),
+        ]);
+
+        assert_eq!(strategies[1].break_even_month, None);
+        assert_eq!(cheapest, BillingMode::Hourly);
+    }
+}
@@ -0,0 +1,187 @@
+use actix_web::web;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::events::Event;
+use crate::AppState;
+
+// Held from triggering a crawl until the crawler reports it finished; clears
+// the flag on drop
+pub struct CrawlGuard<'a> {
+    flag: &'a AtomicBool,
+}
+
+impl<'a> CrawlGuard<'a> {
+    // Returns None if another crawl is already running
+    pub fn acquire(flag: &'a AtomicBool) -> Option<Self> {
+        flag.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| CrawlGuard { flag })
+    }
+}
+
+impl Drop for CrawlGuard<'_> {
+    fn drop(&mut self) {
+        self.flag.store(false, Ordering::Release);
+    }
+}
+
+// How often the crawler is asked whether a triggered crawl has finished, and
+// how long to wait for it before giving up
+const CRAWL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const CRAWL_WAIT_LIMIT: Duration = Duration::from_secs(600);
+
+// Crawler's report on its latest crawl (GET /status)
+#[derive(Debug, Deserialize)]
+struct CrawlerStatus {
+    // "running" or "completed"; empty before the first crawl
+    status: String,
+    // Set when a crawl starts and again when it finishes
+    last_crawl: String,
+}
+
+impl CrawlerStatus {
+    fn running(&self) -> bool {
+        self.status == "running"
+    }
+}
+
+pub enum CrawlOutcome {
+    Completed,
+    // The crawler was already crawling, e.g. its own startup crawl
+    Busy,
+    Failed(&'static str),
+}
+
+// Ask the crawler service to refresh pricing data; it replies before crawling
+async fn request_crawl(config: &Config) -> Result<reqwest::StatusCode, reqwest::Error> {
+    let client = reqwest::Client::new();
+    let resp = client.post(config.crawl_endpoint())
+        .timeout(config.crawler_timeout)
+        .send()
+        .await?;
+    Ok(resp.status())
+}
+
+async fn crawler_status(config: &Config) -> Result<CrawlerStatus, reqwest::Error> {
+    reqwest::Client::new()
+        .get(config.crawl_status_endpoint())
+        .timeout(config.crawler_timeout)
+        .send()
+        .await?
+        .json()
+        .await
+}
+
+// Trigger a crawl and wait until the crawler has finished it, calling
+// `started` once the crawler has accepted it. Called with the CrawlGuard held,
+// so runs don't overlap with each other or with a crawl the crawler started on
+// its own.
+pub async fn run_crawl(config: &Config, started: impl FnOnce()) -> CrawlOutcome {
+    let before = match crawler_status(config).await {
+        Ok(status) if status.running() => return CrawlOutcome::Busy,
+        Ok(status) => status.last_crawl,
+        Err(e) => {
+            log::warn!("Failed to reach crawler: {}", e);
+            return CrawlOutcome::Failed("Crawler service not available");
+        }
+    };
+
+    match request_crawl(config).await {
+        Ok(status) if status.is_success() => started(),
+        Ok(status) => {
+            log::warn!("Crawler returned error: {}", status);
+            return CrawlOutcome::Failed("Crawler returned an error");
+        }
+        Err(e) => {
+            log::warn!("Failed to trigger crawler: {}", e);
+            return CrawlOutcome::Failed("Crawler service not available");
+        }
+    }
+
+    let deadline = tokio::time::Instant::now() + CRAWL_WAIT_LIMIT;
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(CRAWL_POLL_INTERVAL).await;
+        match crawler_status(config).await {
+            Ok(status) if !status.running() && status.last_crawl != before => return CrawlOutcome::Completed,
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to check crawl status: {}", e),
+        }
+    }
+    log::warn!("Crawl did not finish within {:?}", CRAWL_WAIT_LIMIT);
+    CrawlOutcome::Failed("Crawl did not finish in time")
+}
+
+// Background loop that triggers crawls on the configured cron schedule
+pub async fn run(data: web::Data<AppState>) {
+    let Some(schedule) = data.config.crawl_schedule.clone() else {
+        return;
+    };
+
+    loop {
+        let Some(next) = schedule.upcoming(chrono::Utc).next() else {
+            log::warn!("Crawl schedule has no upcoming runs, stopping scheduler");
+            return;
+        };
+        log::info!("Next scheduled crawl at {}", next.to_rfc3339());
+
+        let wait = (next - chrono::Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        let Some(_guard) = CrawlGuard::acquire(&data.crawl_running) else {
+            log::warn!("Skipping scheduled crawl, previous crawl still running");
+            continue;
+        };
+
+        match run_crawl(&data.config, || data.events.publish(Event::crawl_started(true))).await {
+            CrawlOutcome::Completed => {
+                log::info!("Scheduled crawl completed");
+                data.events.publish(Event::crawl_completed(true, "Scheduled crawl completed", true));
+                data.events.publish(Event::prices_updated("crawl"));
+            }
+            CrawlOutcome::Busy => log::warn!("Skipping scheduled crawl, crawler is already crawling"),
+            CrawlOutcome::Failed(message) => {
+                log::warn!("Scheduled crawl failed: {}", message);
+                data.events.publish(Event::crawl_completed(false, message, true));
+            }
+        }
+    }
+}
+
+// How often the trash is checked for expired entries
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+// Background loop that permanently removes trash older than the retention period
+pub async fn purge_trash(data: web::Data<AppState>) {
+    let days = data.config.trash_retention_days;
+    if days == 0 {
+        return;
+    }
+
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+        let db = data.db.lock().unwrap();
+        match db.purge_trash(&cutoff) {
+            Ok((0, 0)) => {}
+            Ok((quotes, items)) => log::info!("Purged {} quotes and {} items from the trash", quotes, items),
+            Err(e) => log::error!("Failed to purge trash: {}", e),
+        }
+    }
+}
+
+// How often idle event streams are pinged
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+// Background loop that keeps event streams open and drops disconnected subscribers
+pub async fn event_keepalive(data: web::Data<AppState>) {
+    let mut interval = tokio::time::interval(KEEPALIVE_INTERVAL);
+    loop {
+        interval.tick().await;
+        data.events.keepalive();
+    }
+}
@@ -0,0 +1,285 @@
+use rusqlite::Result;
+
+use crate::db::Database;
+use crate::models::*;
+
+// Map each workload requirement to the best-matching flavor and disk type
+pub fn size_workloads(db: &Database, req: &SizingRequest) -> Result<(Vec<SizedWorkload>, SizingTotals)> {
+    let mode = req.billing_mode.unwrap_or_default();
+    // Cheapest first
+    let disk_types = db.get_disk_types()?;
+
+    let mut totals = SizingTotals::default();
+    let mut sized = Vec::with_capacity(req.workloads.len());
+
+    for workload in &req.workloads {
+        let query = BestMatchQuery {
+            family: req.family.clone(),
+            billing_mode: Some(mode),
+            max_overshoot: req.max_overshoot,
+            limit: Some(1),
+            ..BestMatchQuery::new(workload.vcpus, workload.ram_gb)
+        };
+
+        let Some(best) = db.find_best_match(&query)?.into_iter().next() else {
+            totals.unmatched += 1;
+            sized.push(SizedWorkload {
+                name: workload.name.clone(),
+                item: None,
+                error: Some(format!(
+                    "No flavor with at least {} vCPU and {} GB RAM",
+                    workload.vcpus, workload.ram_gb
+                )),
+            });
+            continue;
+        };
+
+        // Without a requested tier the cheapest disk type is used, as in the optimizer
+        let disk_size = workload.disk_gb.filter(|gb| *gb > 0);
+        let disk = match disk_size {
+            Some(_) => {
+                let disk = match &workload.disk_tier {
+                    Some(tier) => disk_types.iter().find(|d| &d.id == tier),
+                    None => disk_types.first(),
+                };
+                let Some(disk) = disk else {
+                    totals.unmatched += 1;
+                    sized.push(SizedWorkload {
+                        name: workload.name.clone(),
+                        item: None,
+                        error: Some(match &workload.disk_tier {
+                            Some(tier) => format!("Unknown disk tier '{}'", tier),
+                            None => "No disk types available".to_string(),
+                        }),
+                    });
+                    continue;
+                };
+                Some(disk)
+            }
+            None => None,
+        };
+        let disk_price = disk.zip(disk_size).map(|(d, gb)| d.price_per_gb * gb as f64);
+
+        let flavor = &best.flavor;
+        totals.matched += 1;
+        totals.vcpus += flavor.vcpus;
+        totals.ram_gb += flavor.ram_gb;
+        totals.disk_gb += disk.and(disk_size).unwrap_or(0);
+        totals.compute_monthly += best.flavor.monthly_cost(mode);
+        totals.storage_monthly += disk_price.unwrap_or(0.0);
+
+        sized.push(SizedWorkload {
+            name: workload.name.clone(),
+            item: Some(CreateQuoteItem {
+                flavor_id: Some(flavor.id.clone()),
+                flavor_name: Some(flavor.name.clone()),
+                vcpus: Some(flavor.vcpus),
+                ram_gb: Some(flavor.ram_gb),
+                flavor_price: Some(flavor.price_hourly),
+                disk_type_id: disk.map(|d| d.id.clone()),
+                disk_type_name: disk.map(|d| d.name.clone()),
+                disk_size_gb: disk.and(disk_size),
+                disk_price,
+                hostname: Some(workload.name.clone()),
+                billing_mode: Some(mode),
+                ..Default::default()
+            }),
+            error: None,
+        });
+    }
+
+    totals.total_monthly = totals.compute_monthly + totals.storage_monthly;
+    Ok((sized, totals))
+}
+
+// Suggest smaller flavors for quote items based on observed peak utilization.
+// A key applies to every item with that hostname, or else that code number.
+pub fn rightsize_items(db: &Database, quote_id: &str, req: &RightsizeRequest) -> Result<RightsizeResponse> {
+    let mode = req.billing_mode.unwrap_or_default();
+    let headroom = 1.0 + req.headroom_pct.unwrap_or(20.0).max(0.0) / 100.0;
+    let items = db.get_items(quote_id)?;
+
+    let mut suggestions = Vec::new();
+    let mut unmatched_keys = Vec::new();
+
+    for usage in &req.utilization {
+        let key = Some(usage.key.as_str());
+        let mut matched: Vec<&QuoteItem> = items.iter().filter(|i| i.hostname.as_deref() == key).collect();
+        if matched.is_empty() {
+            matched = items.iter().filter(|i| i.code_number.as_deref() == key).collect();
+        }
+        // Only items with a flavor size can be rightsized
+        matched.retain(|i| i.vcpus.is_some() && i.ram_gb.is_some());
+        if matched.is_empty() {
+            unmatched_keys.push(usage.key.clone());
+            continue;
+        }
+
+        for item in matched {
+            suggestions.push(suggest(db, item, usage, req, mode, headroom)?);
+        }
+    }
+
+    let current_monthly: f64 = suggestions.iter().map(|s| s.current_monthly).sum();
+    let suggested_monthly: f64 = suggestions.iter().map(|s| s.suggested_monthly).sum();
+
+    Ok(RightsizeResponse {
+        quote_id: quote_id.to_string(),
+        billing_mode: mode,
+        suggestions,
+        unmatched_keys,
+        current_monthly,
+        suggested_monthly,
+        savings_monthly: current_monthly - suggested_monthly,
+    })
+}
+
+fn suggest(
+    db: &Database,
+    item: &QuoteItem,
+    usage: &ItemUtilization,
+    req: &RightsizeRequest,
+    mode: BillingMode,
+    headroom: f64,
+) -> Result<RightsizeSuggestion> {
+    let vcpus = item.vcpus.unwrap_or_default();
+    let ram_gb = item.ram_gb.unwrap_or_default();
+
+    let current = match &item.flavor_id {
+        Some(id) => db.get_flavor(id)?,
+        None => None,
+    };
+    // Catalog price for the billing mode, else the stored hourly price discounted the same way
+    let current_monthly = match &current {
+        Some(f) => f.monthly_cost(mode),
+        None => item.flavor_price.unwrap_or(0.0) * HOURS_PER_MONTH * mode.discount_factor(),
+    };
+
+    let required_vcpus = ((vcpus as f64 * usage.cpu_peak_pct / 100.0 * headroom).ceil() as i32).max(1);
+    let required_ram_gb = ram_gb * usage.ram_peak_pct / 100.0 * headroom;
+    let family = req.family.clone().or_else(|| current.as_ref().map(|f| f.family().to_string()));
+
+    let query = BestMatchQuery {
+        family,
+        billing_mode: Some(mode),
+        limit: Some(1),
+        ..BestMatchQuery::new(required_vcpus, required_ram_gb)
+    };
+    // Only suggest a change when it is actually cheaper
+    let suggested = db
+        .find_best_match(&query)?
+        .into_iter()
+        .next()
+        .filter(|m| m.flavor.monthly_cost(mode) < current_monthly);
+    let suggested_monthly = suggested
+        .as_ref()
+        .map(|m| m.flavor.monthly_cost(mode))
+        .unwrap_or(current_monthly);
+
+    Ok(RightsizeSuggestion {
+        item_id: item.id.clone(),
+        key: usage.key.clone(),
+        current_flavor: item.flavor_name.clone(),
+        required_vcpus,
+        required_ram_gb,
+        suggested,
+        current_monthly,
+        suggested_monthly,
+        savings_monthly: current_monthly - suggested_monthly,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{assert_close, database, flavor};
+
+    // Catalog of s6 flavors and an empty quote, returning the quote id
+    fn quote() -> (Database, String) {
+        let db = database();
+        for f in [flavor("s6.small", 1, 2.0, 0.01), flavor("s6.medium", 2, 4.0, 0.02), flavor("s6.large", 4, 8.0, 0.04)] {
+            db.insert_flavor(&f).unwrap();
+        }
+        let quote = db
+            .create_quote(&CreateQuote { name: "q".to_string(), customer_id: None, status: None, tax_profile_id: None })
+            .unwrap();
+        (db, quote.id)
+    }
+
+    fn on_flavor(name: &str, vcpus: i32, ram_gb: f64, price_hourly: f64) -> CreateQuoteItem {
+        CreateQuoteItem {
+            flavor_id: Some(name.to_string()),
+            flavor_name: Some(name.to_string()),
+            vcpus: Some(vcpus),
+            ram_gb: Some(ram_gb),
+            flavor_price: Some(price_hourly),
+            ..Default::default()
+        }
+    }
+
+    fn peak(key: &str, pct: f64) -> ItemUtilization {
+        ItemUtilization { key: key.to_string(), cpu_peak_pct: pct, ram_peak_pct: pct }
+    }
+
+    fn request(utilization: Vec<ItemUtilization>) -> RightsizeRequest {
+        RightsizeRequest { utilization, headroom_pct: None, family: None, billing_mode: None }
+    }
+
+    #[test]
+    fn keys_match_hostnames_before_code_numbers() {
+        let (db, quote_id) = quote();
+        let large = on_flavor("s6.large", 4, 8.0, 0.04);
+        let web = db.create_item(&quote_id, &CreateQuoteItem { hostname: Some("web-1".to_string()), ..large.clone() }).unwrap();
+        // Shares its code number with the other item's hostname, which takes precedence
+        db.create_item(&quote_id, &CreateQuoteItem { code_number: Some("web-1".to_string()), ..large.clone() }).unwrap();
+        let app: Vec<String> = (0..2)
+            .map(|_| db.create_item(&quote_id, &CreateQuoteItem { code_number: Some("APP".to_string()), ..large.clone() }))
+            .map(|item| item.unwrap().id)
+            .collect();
+        // No flavor size to rightsize from
+        db.create_item(&quote_id, &CreateQuoteItem { hostname: Some("notes".to_string()), ..Default::default() }).unwrap();
+
+        let req = request(vec![peak("web-1", 25.0), peak("APP", 25.0), peak("notes", 10.0), peak("ghost", 10.0)]);
+        let result = rightsize_items(&db, &quote_id, &req).unwrap();
+
+        let matched: Vec<&str> = result.suggestions.iter().map(|s| s.item_id.as_str()).collect();
+        assert_eq!(matched, vec![web.id.as_str(), app[0].as_str(), app[1].as_str()]);
+        assert_eq!(result.unmatched_keys, vec!["notes", "ghost"]);
+
+        // 25% of 4 vCPU / 8 GB plus 20% headroom needs 2 vCPU / 2.4 GB
+        let suggestion = &result.suggestions[0];
+        assert_eq!(suggestion.required_vcpus, 2);
+        assert_close(suggestion.required_ram_gb, 2.4);
+        assert_eq!(suggestion.suggested.as_ref().map(|m| m.flavor.name.as_str()), Some("s6.medium"));
+        assert_close(result.current_monthly, 3.0 * 28.8);
+        assert_close(result.savings_monthly, 3.0 * 14.4);
+    }
+
+    #[test]
+    fn nothing_is_suggested_when_no_flavor_is_cheaper() {
+        let (db, quote_id) = quote();
+        let small = on_flavor("s6.small", 1, 2.0, 0.01);
+        db.create_item(&quote_id, &CreateQuoteItem { hostname: Some("busy".to_string()), ..small }).unwrap();
+
+        let result = rightsize_items(&db, &quote_id, &request(vec![peak("busy", 100.0)])).unwrap();
+
+        let suggestion = &result.suggestions[0];
+        assert!(suggestion.suggested.is_none());
+        assert_close(suggestion.suggested_monthly, suggestion.current_monthly);
+        assert_close(result.savings_monthly, 0.0);
+    }
+
+    #[test]
+    fn flavors_missing_from_the_catalog_are_priced_from_the_item() {
+        let (db, quote_id) = quote();
+        let retired = on_flavor("s5.large", 4, 8.0, 0.04);
+        db.create_item(&quote_id, &CreateQuoteItem { hostname: Some("old".to_string()), ..retired }).unwrap();
+
+        let req = RightsizeRequest { billing_mode: Some(BillingMode::Yearly3), ..request(vec![peak("old", 25.0)]) };
+        let result = rightsize_items(&db, &quote_id, &req).unwrap();
+
+        let discount = BillingMode::Yearly3.discount_factor();
+        assert_close(result.current_monthly, 28.8 * discount);
+        assert_close(result.suggested_monthly, 14.4 * discount);
+    }
+}
@@ -0,0 +1,66 @@
+// Fixtures and assertions shared by the unit tests
+
+use crate::db::Database;
+use crate::models::{BillingMode, DiskType, Flavor, QuoteItem, TaxProfile, HOURS_PER_MONTH};
+
+pub fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+}
+
+pub fn tax_profile(rate: f64, inclusive: bool) -> TaxProfile {
+    TaxProfile {
+        id: "vat".to_string(),
+        label: "VAT".to_string(),
+        rate,
+        inclusive,
+        created_at: String::new(),
+    }
+}
+
+// Item of quote "q" with the given fields over monthly billing and no prices
+pub fn quote_item(id: &str, fields: serde_json::Value) -> QuoteItem {
+    let mut item = serde_json::json!({
+        "id": id,
+        "quote_id": "q",
+        "billing_mode": "monthly",
+        "position": 0,
+        "created_at": "",
+        "updated_at": "",
+    });
+    if let (Some(item), Some(fields)) = (item.as_object_mut(), fields.as_object()) {
+        item.extend(fields.clone());
+    }
+    serde_json::from_value(item).unwrap()
+}
+
+// Empty in-memory database with the full schema
+pub fn database() -> Database {
+    Database::new(":memory:").unwrap()
+}
+
+// Catalog flavor, named like "s6.large", with the crawler's derived period prices
+pub fn flavor(name: &str, vcpus: i32, ram_gb: f64, price_hourly: f64) -> Flavor {
+    let monthly = price_hourly * HOURS_PER_MONTH;
+    Flavor {
+        id: name.to_string(),
+        name: name.to_string(),
+        vcpus,
+        ram_gb,
+        price_hourly,
+        price_monthly: monthly,
+        price_yearly_1: monthly * 12.0 * BillingMode::Yearly1.discount_factor(),
+        price_yearly_3: monthly * 36.0 * BillingMode::Yearly3.discount_factor(),
+        region: "test".to_string(),
+        created_at: String::new(),
+    }
+}
+
+pub fn disk_type(id: &str, price_per_gb: f64) -> DiskType {
+    DiskType {
+        id: id.to_string(),
+        name: id.to_uppercase(),
+        price_per_gb,
+        region: "test".to_string(),
+        created_at: String::new(),
+    }
+}
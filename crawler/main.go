@@ -49,12 +49,35 @@ type DiskType struct {
 	CreatedAt  string  `json:"created_at"`
 }
 
+// EIP/bandwidth pricing: fixed bandwidth is billed per Mbps, traffic per GB
+type BandwidthType struct {
+	ID              string  `json:"id"`
+	Name            string  `json:"name"`
+	PricePerIPMonth float64 `json:"price_per_ip_month"`
+	PricePerMbps    float64 `json:"price_per_mbps"`
+	PricePerGB      float64 `json:"price_per_gb"`
+	Region          string  `json:"region"`
+	CreatedAt       string  `json:"created_at"`
+}
+
+// OS image licence pricing, charged per vCPU per hour on top of the flavor price
+type Image struct {
+	ID               string  `json:"id"`
+	Name             string  `json:"name"`
+	OSFamily         string  `json:"os_family"`
+	PricePerVCPUHour float64 `json:"price_per_vcpu_hour"`
+	Region           string  `json:"region"`
+	CreatedAt        string  `json:"created_at"`
+}
+
 type CrawlStatus struct {
-	Status       string    `json:"status"`
-	LastCrawl    time.Time `json:"last_crawl"`
-	FlavorsCount int       `json:"flavors_count"`
-	DisksCount   int       `json:"disks_count"`
-	Error        string    `json:"error,omitempty"`
+	Status          string    `json:"status"`
+	LastCrawl       time.Time `json:"last_crawl"`
+	FlavorsCount    int       `json:"flavors_count"`
+	DisksCount      int       `json:"disks_count"`
+	BandwidthsCount int       `json:"bandwidths_count"`
+	ImagesCount     int       `json:"images_count"`
+	Error           string    `json:"error,omitempty"`
 }
 
 var (
@@ -132,6 +155,25 @@ func initDB() error {
 			region TEXT NOT NULL,
 			created_at TEXT NOT NULL
 		);
+
+		CREATE TABLE IF NOT EXISTS bandwidth_types (
+			id TEXT PRIMARY KEY,
+			name TEXT NOT NULL,
+			price_per_ip_month REAL NOT NULL,
+			price_per_mbps REAL NOT NULL,
+			price_per_gb REAL NOT NULL,
+			region TEXT NOT NULL,
+			created_at TEXT NOT NULL
+		);
+
+		CREATE TABLE IF NOT EXISTS images (
+			id TEXT PRIMARY KEY,
+			name TEXT NOT NULL,
+			os_family TEXT NOT NULL,
+			price_per_vcpu_hour REAL NOT NULL,
+			region TEXT NOT NULL,
+			created_at TEXT NOT NULL
+		);
 	`)
 
 	return err
@@ -179,6 +221,8 @@ func performCrawl() {
 
 	flavors := getIstanbulFlavors()
 	disks := getIstanbulDiskTypes()
+	bandwidths := getIstanbulBandwidthTypes()
+	images := getIstanbulImages()
 
 	flavorsSaved := 0
 	for _, f := range flavors {
@@ -198,14 +242,34 @@ func performCrawl() {
 		}
 	}
 
+	bandwidthsSaved := 0
+	for _, b := range bandwidths {
+		if err := saveBandwidthType(b); err != nil {
+			log.Printf("Failed to save bandwidth type %s: %v", b.ID, err)
+		} else {
+			bandwidthsSaved++
+		}
+	}
+
+	imagesSaved := 0
+	for _, i := range images {
+		if err := saveImage(i); err != nil {
+			log.Printf("Failed to save image %s: %v", i.ID, err)
+		} else {
+			imagesSaved++
+		}
+	}
+
 	lastStatus = CrawlStatus{
-		Status:       "completed",
-		LastCrawl:    time.Now(),
-		FlavorsCount: flavorsSaved,
-		DisksCount:   disksSaved,
+		Status:          "completed",
+		LastCrawl:       time.Now(),
+		FlavorsCount:    flavorsSaved,
+		DisksCount:      disksSaved,
+		BandwidthsCount: bandwidthsSaved,
+		ImagesCount:     imagesSaved,
 	}
 
-	log.Printf("Crawl completed: %d flavors, %d disk types saved", flavorsSaved, disksSaved)
+	log.Printf("Crawl completed: %d flavors, %d disk types, %d bandwidth types, %d images saved", flavorsSaved, disksSaved, bandwidthsSaved, imagesSaved)
 }
 
 // Create flavor with pricing tiers
@@ -277,6 +341,29 @@ func getIstanbulDiskTypes() []DiskType {
 	}
 }
 
+func getIstanbulBandwidthTypes() []BandwidthType {
+	now := time.Now().UTC().Format(time.RFC3339)
+
+	return []BandwidthType{
+		{ID: "5_bgp", Name: "Dynamic BGP", PricePerIPMonth: 3.00, PricePerMbps: 8.00, PricePerGB: 0.080, Region: region, CreatedAt: now},
+		{ID: "5_sbgp", Name: "Static BGP", PricePerIPMonth: 3.00, PricePerMbps: 6.50, PricePerGB: 0.070, Region: region, CreatedAt: now},
+	}
+}
+
+func getIstanbulImages() []Image {
+	now := time.Now().UTC().Format(time.RFC3339)
+
+	return []Image{
+		{ID: "ubuntu-22.04", Name: "Ubuntu 22.04 LTS", OSFamily: "linux", PricePerVCPUHour: 0, Region: region, CreatedAt: now},
+		{ID: "centos-7.9", Name: "CentOS 7.9", OSFamily: "linux", PricePerVCPUHour: 0, Region: region, CreatedAt: now},
+		{ID: "huawei-euleros-2.0", Name: "Huawei EulerOS 2.0", OSFamily: "linux", PricePerVCPUHour: 0, Region: region, CreatedAt: now},
+		{ID: "rhel-8.6", Name: "Red Hat Enterprise Linux 8.6", OSFamily: "rhel", PricePerVCPUHour: 0.0150, Region: region, CreatedAt: now},
+		{ID: "sles-15", Name: "SUSE Linux Enterprise Server 15", OSFamily: "suse", PricePerVCPUHour: 0.0125, Region: region, CreatedAt: now},
+		{ID: "windows-2019-std", Name: "Windows Server 2019 Standard", OSFamily: "windows", PricePerVCPUHour: 0.0230, Region: region, CreatedAt: now},
+		{ID: "windows-2022-std", Name: "Windows Server 2022 Standard", OSFamily: "windows", PricePerVCPUHour: 0.0230, Region: region, CreatedAt: now},
+	}
+}
+
 func saveFlavor(f Flavor) error {
 	_, err := db.Exec(`
 		INSERT OR REPLACE INTO flavors (id, name, vcpus, ram_gb, price_hourly, price_monthly, price_yearly_1, price_yearly_3, region, created_at)
@@ -292,3 +379,19 @@ func saveDiskType(d DiskType) error {
 	`, d.ID, d.Name, d.PricePerGB, d.Region, d.CreatedAt)
 	return err
 }
+
+func saveBandwidthType(b BandwidthType) error {
+	_, err := db.Exec(`
+		INSERT OR REPLACE INTO bandwidth_types (id, name, price_per_ip_month, price_per_mbps, price_per_gb, region, created_at)
+		VALUES (?, ?, ?, ?, ?, ?, ?)
+	`, b.ID, b.Name, b.PricePerIPMonth, b.PricePerMbps, b.PricePerGB, b.Region, b.CreatedAt)
+	return err
+}
+
+func saveImage(i Image) error {
+	_, err := db.Exec(`
+		INSERT OR REPLACE INTO images (id, name, os_family, price_per_vcpu_hour, region, created_at)
+		VALUES (?, ?, ?, ?, ?, ?)
+	`, i.ID, i.Name, i.OSFamily, i.PricePerVCPUHour, i.Region, i.CreatedAt)
+	return err
+}
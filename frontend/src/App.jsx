@@ -13,6 +13,7 @@ import { usePricing } from './hooks/usePricing'
 import { useQuote } from './hooks/useQuote'
 import { useDarkMode } from './hooks/useDarkMode'
 import { useKeyboardShortcuts, KeyboardShortcutsHelp } from './hooks/useKeyboardShortcuts.jsx'
+import { quoteApi } from './utils/api'
 
 // Verbosity context for controlling toast detail level
 const VerbosityContext = createContext({ verbose: false, setVerbose: () => {} })
@@ -202,34 +203,22 @@ function AppContent() {
     return hashArray.map(b => b.toString(16).padStart(2, '0')).join('')
   }
 
-  const handleExportCSV = useCallback(() => {
+  const handleExportCSV = useCallback(async () => {
     if (!currentQuote || items.length === 0) return
 
-    const headers = ['Hostname', 'Code', 'Instance Type', 'vCPUs', 'RAM (GB)', 'Disk Type', 'Disk (GB)', 'Description', 'Hourly ($)', 'Monthly ($)']
-    const rows = items.map(item => [
-      item.hostname || '',
-      item.code_number || '',
-      item.flavor_name,
-      item.vcpus,
-      item.ram_gb,
-      item.disk_type_name || '',
-      item.disk_size_gb || '',
-      item.description || '',
-      item.flavor_price?.toFixed(4) || '0',
-      ((item.flavor_price || 0) * 720 + (item.disk_price || 0)).toFixed(2)
-    ])
-
-    const csv = [headers.join(','), ...rows.map(r => r.map(c => `"${c}"`).join(','))].join('\n')
-    const blob = new Blob([csv], { type: 'text/csv' })
-    const url = URL.createObjectURL(blob)
-    const a = document.createElement('a')
-    a.href = url
-    const filename = `${currentQuote.name.replace(/\s+/g, '_')}_quote.csv`
-    a.download = filename
-    a.click()
-    URL.revokeObjectURL(url)
-    toast.success('CSV exported')
-    if (verbose) toast.info(`File: ${filename}`)
+    try {
+      const { blob, filename } = await quoteApi.exportCsv(currentQuote.id)
+      const url = URL.createObjectURL(blob)
+      const a = document.createElement('a')
+      a.href = url
+      a.download = filename
+      a.click()
+      URL.revokeObjectURL(url)
+      toast.success('CSV exported')
+      if (verbose) toast.info(`File: ${filename}`)
+    } catch (e) {
+      toast.error('Failed to export CSV')
+    }
   }, [currentQuote, items, toast, verbose])
 
   const handleImportCSV = useCallback(async () => {
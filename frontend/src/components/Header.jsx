@@ -1,5 +1,5 @@
-import React from 'react'
-import { Upload, Download, BarChart3, MessageSquare, Sun, Moon, HelpCircle, RefreshCw } from 'lucide-react'
+import React, { useState } from 'react'
+import { Upload, Download, BarChart3, MessageSquare, Sun, Moon, HelpCircle, RefreshCw, User } from 'lucide-react'
 import { useVerbosity } from '../App'
 
 export function Header({
@@ -13,6 +13,20 @@ export function Header({
   onToggleAnalytics
 }) {
   const { verbose, toggleVerbose } = useVerbosity()
+  // Name sent as X-Actor and recorded in the quote change history
+  const [actor, setActor] = useState(() => localStorage.getItem('quotator-actor') || '')
+
+  const handleSetActor = () => {
+    const name = window.prompt('Your name for the change history', actor)
+    if (name === null) return
+    const trimmed = name.trim()
+    if (trimmed) {
+      localStorage.setItem('quotator-actor', trimmed)
+    } else {
+      localStorage.removeItem('quotator-actor')
+    }
+    setActor(trimmed)
+  }
 
   return (
     <header className="header">
@@ -57,6 +71,13 @@ export function Header({
         >
           {isDark ? <Sun size={18} /> : <Moon size={18} />}
         </button>
+        <button
+          className={`header-btn icon-btn ${actor ? 'active' : ''}`}
+          onClick={handleSetActor}
+          title={actor ? `Changes recorded as ${actor}` : 'Set your name for the change history'}
+        >
+          <User size={18} />
+        </button>
         <div className="header-separator" />
         <button
           className="header-btn icon-btn"
@@ -1,5 +1,5 @@
 import React from 'react'
-import { Trash2, Copy, Plus } from 'lucide-react'
+import { Trash2, Copy, Plus, MessageSquare } from 'lucide-react'
 import { QuoteFilter } from './SearchFilter'
 import { SkeletonQuoteList } from './Skeleton'
 
@@ -48,6 +48,15 @@ export function QuoteSelector({
               onClick={() => onSelect(quote.id)}
             >
               <span className="quote-item-name">{quote.name}</span>
+              {quote.comment_count > 0 && (
+                <span
+                  className="quote-item-comments"
+                  title={`${quote.open_comment_count} unresolved of ${quote.comment_count} comments`}
+                >
+                  <MessageSquare size={12} />
+                  {quote.open_comment_count}/{quote.comment_count}
+                </span>
+              )}
               <button
                 className="btn-icon btn-delete"
                 onClick={(e) => {
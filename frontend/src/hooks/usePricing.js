@@ -55,6 +55,13 @@ export function usePricing() {
     loadPricing()
   }, [loadPricing])
 
+  // Reload when prices change elsewhere, e.g. after a scheduled crawl
+  useEffect(() => {
+    const source = pricingApi.subscribe()
+    source.addEventListener('prices_updated', () => loadPricing())
+    return () => source.close()
+  }, [loadPricing])
+
   return { flavors, diskTypes, loading, error, refreshPricing }
 }
 
@@ -2,12 +2,28 @@ import { useState, useEffect, useCallback, useRef } from 'react'
 import { quoteApi, quoteItemApi } from '../utils/api'
 import cache from '../utils/cache'
 
+// Save changes made against a known version. When someone else saved in between
+// (409), the same changes are sent again on top of their version, so fields not
+// edited here keep their change. Resolves to the new version and, after such a
+// merge, the server state the changes were applied to.
+async function saveMerging(update, changes, version) {
+  try {
+    return { version: await update(changes, version), current: null }
+  } catch (e) {
+    if (e.status !== 409 || !e.data?.current) throw e
+    const current = e.data.current
+    return { version: await update(changes, current.version), current }
+  }
+}
+
 export function useQuote() {
   const [quotes, setQuotes] = useState([])
   const [currentQuote, setCurrentQuote] = useState(null)
   const [items, setItems] = useState([])
   const [loading, setLoading] = useState(false)
   const saveTimeoutRef = useRef({})
+  // Item fields edited locally and not yet sent, by item id
+  const pendingEditsRef = useRef({})
 
   // Load all quotes
   const loadQuotes = useCallback(async () => {
@@ -74,14 +90,25 @@ export function useQuote() {
       clearTimeout(saveTimeoutRef.current.quote)
     }
 
-    saveTimeoutRef.current.quote = setTimeout(async () => {
+    const quoteId = currentQuote.id
+    const timeoutId = setTimeout(async () => {
       try {
-        await quoteApi.updateQuote(currentQuote.id, updated)
-        setQuotes(prev => prev.map(q => q.id === currentQuote.id ? updated : q))
+        const { version, current } = await saveMerging(
+          (changes, v) => quoteApi.updateQuote(quoteId, changes, v),
+          { name },
+          currentQuote.version
+        )
+        const saved = { ...(current || updated), name, version }
+        setQuotes(prev => prev.map(q => q.id === quoteId ? saved : q))
+        // Keeps anything typed while the save was in flight
+        setCurrentQuote(prev => prev?.id === quoteId ? { ...saved, name: prev.name } : prev)
       } catch (e) {
         console.error('Failed to save quote:', e)
+      } finally {
+        if (saveTimeoutRef.current.quote === timeoutId) delete saveTimeoutRef.current.quote
       }
     }, 500)
+    saveTimeoutRef.current.quote = timeoutId
   }, [currentQuote])
 
   // Add item to quote
@@ -109,6 +136,7 @@ export function useQuote() {
     )
     setItems(updatedItems)
     cache.set('items', currentQuote.id, updatedItems)
+    pendingEditsRef.current[itemId] = { ...pendingEditsRef.current[itemId], ...updates }
 
     // Debounced API save
     const timeoutKey = `item-${itemId}`
@@ -116,14 +144,31 @@ export function useQuote() {
       clearTimeout(saveTimeoutRef.current[timeoutKey])
     }
 
-    saveTimeoutRef.current[timeoutKey] = setTimeout(async () => {
+    const quoteId = currentQuote.id
+    const timeoutId = setTimeout(async () => {
+      const changes = pendingEditsRef.current[itemId]
+      delete pendingEditsRef.current[itemId]
       try {
         const item = updatedItems.find(i => i.id === itemId)
-        await quoteItemApi.updateItem(currentQuote.id, itemId, item)
+        const { version, current } = await saveMerging(
+          (edits, v) => quoteItemApi.updateItem(quoteId, itemId, edits, v),
+          changes,
+          item.version
+        )
+        setItems(prev => {
+          const next = prev.map(i => i.id !== itemId ? i : current
+            ? { ...current, ...changes, ...pendingEditsRef.current[itemId], version }
+            : { ...i, version })
+          cache.set('items', quoteId, next)
+          return next
+        })
       } catch (e) {
         console.error('Failed to save item:', e)
+      } finally {
+        if (saveTimeoutRef.current[timeoutKey] === timeoutId) delete saveTimeoutRef.current[timeoutKey]
       }
     }, 500)
+    saveTimeoutRef.current[timeoutKey] = timeoutId
   }, [currentQuote, items])
 
   // Delete item
@@ -181,7 +226,9 @@ export function useQuote() {
 
       // Create new quote with copy name
       const newQuote = await quoteApi.createQuote({
-        name: `${originalQuote.name} (Copy)`
+        name: `${originalQuote.name} (Copy)`,
+        customer_id: originalQuote.customer_id,
+        tax_profile_id: originalQuote.tax_profile_id
       })
 
       // Get original items
@@ -201,7 +248,30 @@ export function useQuote() {
           disk_price: item.disk_price,
           hostname: item.hostname,
           code_number: item.code_number,
-          description: item.description
+          description: item.description,
+          billing_mode: item.billing_mode,
+          usage_hours_per_month: item.usage_hours_per_month,
+          usage_schedule: item.usage_schedule,
+          sku_id: item.sku_id,
+          sku_category: item.sku_category,
+          sku_name: item.sku_name,
+          sku_unit: item.sku_unit,
+          sku_price: item.sku_price,
+          sku_quantity: item.sku_quantity,
+          bandwidth_type_id: item.bandwidth_type_id,
+          bandwidth_type_name: item.bandwidth_type_name,
+          eip_count: item.eip_count,
+          bandwidth_mbps: item.bandwidth_mbps,
+          traffic_gb: item.traffic_gb,
+          bandwidth_price: item.bandwidth_price,
+          os_image: item.os_image,
+          os_image_name: item.os_image_name,
+          os_licence_price: item.os_licence_price,
+          backup_policy_id: item.backup_policy_id,
+          backup_policy_name: item.backup_policy_name,
+          backup_retention_days: item.backup_retention_days,
+          backup_change_rate_pct: item.backup_change_rate_pct,
+          backup_price_per_gb: item.backup_price_per_gb
         }))
       )
 
@@ -223,6 +293,66 @@ export function useQuote() {
     loadQuotes()
   }, [loadQuotes])
 
+  // Apply changes made by others to the selected quote as they happen; an
+  // item or name with a save still pending keeps the local edit
+  const currentQuoteId = currentQuote?.id
+  useEffect(() => {
+    if (!currentQuoteId) return
+
+    const source = quoteApi.subscribe(currentQuoteId)
+    const changeItems = (change) => setItems(prev => {
+      const next = change(prev)
+      cache.set('items', currentQuoteId, next)
+      return next
+    })
+    const upsertItem = (event) => {
+      const item = JSON.parse(event.data)
+      if (saveTimeoutRef.current[`item-${item.id}`]) return
+      changeItems(prev => prev.some(i => i.id === item.id)
+        ? prev.map(i => i.id === item.id ? item : i)
+        : [...prev, item])
+    }
+
+    source.addEventListener('item_created', upsertItem)
+    source.addEventListener('item_updated', upsertItem)
+    source.addEventListener('item_deleted', (event) => {
+      const { id } = JSON.parse(event.data)
+      changeItems(prev => prev.filter(i => i.id !== id))
+    })
+    source.addEventListener('items_reordered', (event) => {
+      const { item_ids } = JSON.parse(event.data)
+      changeItems(prev => [...prev].sort((a, b) => item_ids.indexOf(a.id) - item_ids.indexOf(b.id)))
+    })
+    source.addEventListener('quote_updated', (event) => {
+      const quote = JSON.parse(event.data)
+      setQuotes(prev => prev.map(q => q.id === quote.id ? { ...q, ...quote } : q))
+      if (!saveTimeoutRef.current.quote) setCurrentQuote(quote)
+    })
+    // Keeps the quote list's comment badge current
+    const refreshCommentCounts = async () => {
+      try {
+        const comments = await quoteApi.getComments(currentQuoteId)
+        const counts = {
+          comment_count: comments.length,
+          open_comment_count: comments.filter(c => !c.resolved).length
+        }
+        setQuotes(prev => prev.map(q => q.id === currentQuoteId ? { ...q, ...counts } : q))
+      } catch (e) {
+        console.error('Failed to refresh comment counts:', e)
+      }
+    }
+    source.addEventListener('comment_created', refreshCommentCounts)
+    source.addEventListener('comment_updated', refreshCommentCounts)
+    source.addEventListener('comment_deleted', refreshCommentCounts)
+    source.addEventListener('quote_deleted', () => {
+      setQuotes(prev => prev.filter(q => q.id !== currentQuoteId))
+      setCurrentQuote(null)
+      setItems([])
+    })
+
+    return () => source.close()
+  }, [currentQuoteId])
+
   return {
     quotes,
     currentQuote,
@@ -23,32 +23,57 @@ function debounce(key, fn, delay = 500) {
   })
 }
 
-// Generic API request
-async function request(endpoint, options = {}) {
+// Generic API request, resolving to the raw response. Failed requests throw an
+// error carrying the status and the parsed error body (e.g. `current` on a 409).
+async function send(endpoint, options = {}) {
   const url = `${API_BASE}${endpoint}`
+  // Name recorded in the quote audit log, when one has been set
+  const actor = localStorage.getItem('quotator-actor')
 
   const config = {
+    ...options,
     headers: {
       'Content-Type': 'application/json',
+      ...(actor && { 'X-Actor': actor }),
       ...options.headers
-    },
-    ...options
+    }
   }
 
   try {
     const response = await fetch(url, config)
 
     if (!response.ok) {
-      throw new Error(`API error: ${response.status}`)
+      const error = new Error(`API error: ${response.status}`)
+      error.status = response.status
+      error.data = await response.json().catch(() => null)
+      throw error
     }
 
-    return await response.json()
+    return response
   } catch (e) {
     console.error('API request failed:', e)
     throw e
   }
 }
 
+// Generic API request, resolving to the JSON body
+async function request(endpoint, options = {}) {
+  const response = await send(endpoint, options)
+  return response.json()
+}
+
+// Update guarded by the version the changes were made against, so a change by
+// someone else in between is rejected with 409; resolves to the new version
+async function versionedUpdate(endpoint, changes, version) {
+  const response = await send(endpoint, {
+    method: 'PUT',
+    headers: version != null ? { 'If-Match': `"${version}"` } : {},
+    body: JSON.stringify(changes)
+  })
+  const etag = response.headers.get('ETag')
+  return etag ? Number(etag.replace(/"/g, '')) : null
+}
+
 // Pricing API
 export const pricingApi = {
   // Get ECS flavors
@@ -74,6 +99,11 @@ export const pricingApi = {
   // Trigger crawl refresh
   async refreshPricing() {
     return request('/crawl', { method: 'POST' })
+  },
+
+  // Crawl completions and price changes (server-sent events)
+  subscribe() {
+    return new EventSource(`${API_BASE}/events`)
   }
 }
 
@@ -97,19 +127,37 @@ export const quoteApi = {
     })
   },
 
-  // Update quote (debounced)
-  async updateQuote(id, quote) {
+  // Update quote fields (debounced); resolves to the new version
+  async updateQuote(id, changes, version) {
     return debounce(`quote-${id}`, () =>
-      request(`/quotes/${id}`, {
-        method: 'PUT',
-        body: JSON.stringify(quote)
-      })
+      versionedUpdate(`/quotes/${id}`, changes, version)
     )
   },
 
   // Delete quote
   async deleteQuote(id) {
     return request(`/quotes/${id}`, { method: 'DELETE' })
+  },
+
+  // Comments on the quote and its items (items in the trash excluded)
+  async getComments(id) {
+    return request(`/quotes/${id}/comments`)
+  },
+
+  // CSV export priced by the server; returns the file and its suggested name
+  async exportCsv(id) {
+    const response = await fetch(`${API_BASE}/quotes/${id}/export`)
+    if (!response.ok) {
+      throw new Error(`API error: ${response.status}`)
+    }
+    const disposition = response.headers.get('Content-Disposition') || ''
+    const filename = disposition.match(/filename="([^"]+)"/)?.[1] || 'quote.csv'
+    return { blob: await response.blob(), filename }
+  },
+
+  // Live changes to a quote and its items (server-sent events)
+  subscribe(id) {
+    return new EventSource(`${API_BASE}/quotes/${id}/events`)
   }
 }
 
@@ -128,13 +176,10 @@ export const quoteItemApi = {
     })
   },
 
-  // Update item (debounced for typing)
-  async updateItem(quoteId, itemId, item) {
+  // Update item fields (debounced for typing); resolves to the new version
+  async updateItem(quoteId, itemId, changes, version) {
     return debounce(`item-${itemId}`, () =>
-      request(`/quotes/${quoteId}/items/${itemId}`, {
-        method: 'PUT',
-        body: JSON.stringify(item)
-      })
+      versionedUpdate(`/quotes/${quoteId}/items/${itemId}`, changes, version)
     )
   },
 
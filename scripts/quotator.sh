@@ -8,6 +8,7 @@ DATA_DIR="$PROJECT_DIR/data"
 LOG_DIR="$DATA_DIR/logs"
 
 export DB_PATH="$DATA_DIR/quotator.db"
+export BLUEPRINTS_DIR="$PROJECT_DIR/api/blueprints"
 
 # Colors
 RED='\033[0;31m'
@@ -22,9 +22,9 @@ WARNINGS=0
 
 log() { echo -e "$1"; echo "$1" | sed 's/\x1b\[[0-9;]*m//g' >> "$REPORT_FILE"; }
 section() { log "\n${CYAN}═══════════════════════════════════════════════════════════════${NC}"; log "${CYAN}  $1${NC}"; log "${CYAN}═══════════════════════════════════════════════════════════════${NC}\n"; }
-pass() { ((PASSED++)); log "${GREEN}[PASS]${NC} $1"; }
-fail() { ((FAILED++)); log "${RED}[FAIL]${NC} $1"; }
-warn() { ((WARNINGS++)); log "${YELLOW}[WARN]${NC} $1"; }
+pass() { PASSED=$((PASSED + 1)); log "${GREEN}[PASS]${NC} $1"; }
+fail() { FAILED=$((FAILED + 1)); log "${RED}[FAIL]${NC} $1"; }
+warn() { WARNINGS=$((WARNINGS + 1)); log "${YELLOW}[WARN]${NC} $1"; }
 info() { log "${BLUE}[INFO]${NC} $1"; }
 
 # Initialize report
@@ -392,6 +392,163 @@ else
     warn "Price format may need review"
 fi
 
+section "8. QUOTE WORKFLOW TESTS"
+
+# Request helper for this section: sets RESP_CODE and RESP_BODY
+api() {
+    local method=$1 path=$2 data=$3
+    shift 3
+    local response
+    response=$(curl -s -w "\n%{http_code}" -X "$method" "$API_URL$path" \
+        -H "Content-Type: application/json" "$@" ${data:+-d "$data"} 2>/dev/null)
+    RESP_CODE=$(echo "$response" | tail -1)
+    RESP_BODY=$(echo "$response" | sed '$d')
+}
+
+first_id() { echo "$1" | grep -o '"id":"[^"]*"' | head -1 | cut -d'"' -f4; }
+
+ITEM_JSON='{"flavor_id":"s6.large.2","disk_type_id":"ssd","disk_size_gb":100,"hostname":"wf-server"}'
+
+api POST /quotes '{"name":"Workflow Test Quote"}'
+WF_QUOTE=$(first_id "$RESP_BODY")
+
+if [ "$RESP_CODE" = "201" ] && [ -n "$WF_QUOTE" ]; then
+    api POST "/quotes/$WF_QUOTE/items" "$ITEM_JSON"; WF_ITEM_A=$(first_id "$RESP_BODY")
+    api POST "/quotes/$WF_QUOTE/items" "$ITEM_JSON"; WF_ITEM_B=$(first_id "$RESP_BODY")
+
+    # Sections
+    info "Testing sections..."
+    api POST "/quotes/$WF_QUOTE/sections" '{"name":"Web tier"}'
+    WF_SECTION=$(first_id "$RESP_BODY")
+    if [ "$RESP_CODE" = "201" ]; then
+        pass "Section creation (HTTP $RESP_CODE)"
+    else
+        fail "Section creation (HTTP $RESP_CODE)"
+    fi
+
+    api PUT "/quotes/$WF_QUOTE/items/$WF_ITEM_A" "{\"section_id\":\"$WF_SECTION\"}"
+    api GET "/quotes/$WF_QUOTE/totals" ""
+    if [ "$RESP_CODE" = "200" ] && echo "$RESP_BODY" | grep -q "\"section_id\":\"$WF_SECTION\""; then
+        pass "Totals include the section subtotal"
+    else
+        fail "Totals missing the section subtotal (HTTP $RESP_CODE)"
+    fi
+
+    # Reorder
+    info "Testing item reorder..."
+    api PUT "/quotes/$WF_QUOTE/items/order" "{\"item_ids\":[\"$WF_ITEM_B\",\"$WF_ITEM_A\"]}"
+    api GET "/quotes/$WF_QUOTE/items" ""
+    if [ "$(first_id "$RESP_BODY")" = "$WF_ITEM_B" ]; then
+        pass "Items listed in the new order"
+    else
+        fail "Item reorder not applied"
+    fi
+
+    api PUT "/quotes/$WF_QUOTE/items/order" "{\"item_ids\":[\"$WF_ITEM_B\"]}"
+    if [ "$RESP_CODE" = "400" ]; then
+        pass "Partial item order rejected (HTTP $RESP_CODE)"
+    else
+        fail "Partial item order accepted (HTTP $RESP_CODE)"
+    fi
+
+    # Undo/redo
+    info "Testing undo/redo..."
+    api POST "/quotes/$WF_QUOTE/undo" ""
+    undo_code=$RESP_CODE
+    api GET "/quotes/$WF_QUOTE/items" ""
+    if [ "$undo_code" = "200" ] && [ "$(first_id "$RESP_BODY")" = "$WF_ITEM_A" ]; then
+        pass "Undo reverts the reorder"
+    else
+        fail "Undo did not revert the reorder (HTTP $undo_code)"
+    fi
+
+    api POST "/quotes/$WF_QUOTE/redo" ""
+    redo_code=$RESP_CODE
+    api GET "/quotes/$WF_QUOTE/items" ""
+    if [ "$redo_code" = "200" ] && [ "$(first_id "$RESP_BODY")" = "$WF_ITEM_B" ]; then
+        pass "Redo reapplies the reorder"
+    else
+        fail "Redo did not reapply the reorder (HTTP $redo_code)"
+    fi
+
+    api POST "/quotes/$WF_QUOTE/redo" ""
+    if [ "$RESP_CODE" = "400" ]; then
+        pass "Redo with nothing to redo rejected (HTTP $RESP_CODE)"
+    else
+        fail "Redo with nothing to redo (HTTP $RESP_CODE)"
+    fi
+
+    # Optimistic concurrency
+    info "Testing If-Match version checks..."
+    api PUT "/quotes/$WF_QUOTE" '{"name":"Stale edit"}' -H 'If-Match: "9999"'
+    if [ "$RESP_CODE" = "409" ] && echo "$RESP_BODY" | grep -q '"current"'; then
+        pass "Stale quote update rejected with the current state (HTTP $RESP_CODE)"
+    else
+        fail "Stale quote update (HTTP $RESP_CODE)"
+    fi
+
+    api PUT "/quotes/$WF_QUOTE/items/$WF_ITEM_A" '{"hostname":"stale"}' -H 'If-Match: "9999"'
+    if [ "$RESP_CODE" = "409" ] && echo "$RESP_BODY" | grep -q 'VERSION_CONFLICT'; then
+        pass "Stale item update rejected (HTTP $RESP_CODE)"
+    else
+        fail "Stale item update (HTTP $RESP_CODE)"
+    fi
+
+    etag=$(curl -s -D - -o /dev/null "$API_URL/quotes/$WF_QUOTE" | grep -i '^etag:' | cut -d' ' -f2 | tr -d '\r')
+    api PUT "/quotes/$WF_QUOTE" '{"name":"Workflow Test Quote"}' -H "If-Match: $etag"
+    if [ "$RESP_CODE" = "200" ]; then
+        pass "Update with the current ETag accepted (HTTP $RESP_CODE)"
+    else
+        fail "Update with the current ETag (HTTP $RESP_CODE)"
+    fi
+
+    # Trash and restore
+    info "Testing trash and restore..."
+    api DELETE "/quotes/$WF_QUOTE/items/$WF_ITEM_B" ""
+    api GET /trash ""
+    if echo "$RESP_BODY" | grep -q "$WF_ITEM_B"; then
+        pass "Deleted item listed in the trash"
+    else
+        fail "Deleted item missing from the trash"
+    fi
+
+    api POST "/quotes/$WF_QUOTE/items/$WF_ITEM_B/restore" ""
+    if [ "$RESP_CODE" = "200" ]; then
+        pass "Item restored from the trash (HTTP $RESP_CODE)"
+    else
+        fail "Item restore (HTTP $RESP_CODE)"
+    fi
+
+    # Comments
+    info "Testing comments..."
+    api POST "/quotes/$WF_QUOTE/comments" "{\"body\":\"Check sizing\",\"item_id\":\"$WF_ITEM_A\"}"
+    WF_COMMENT=$(first_id "$RESP_BODY")
+    if [ "$RESP_CODE" = "201" ]; then
+        pass "Comment creation (HTTP $RESP_CODE)"
+    else
+        fail "Comment creation (HTTP $RESP_CODE)"
+    fi
+
+    api PUT "/quotes/$WF_QUOTE/comments/$WF_COMMENT" '{"resolved":true}'
+    api GET "/quotes/$WF_QUOTE/comments?resolved=true" ""
+    if echo "$RESP_BODY" | grep -q "$WF_COMMENT"; then
+        pass "Comment resolved"
+    else
+        fail "Comment not resolved (HTTP $RESP_CODE)"
+    fi
+
+    api GET /quotes ""
+    if echo "$RESP_BODY" | grep -q '"comment_count":1,"open_comment_count":0'; then
+        pass "Quote list carries comment counts"
+    else
+        fail "Quote list comment counts missing"
+    fi
+
+    api DELETE "/quotes/$WF_QUOTE" ""
+else
+    fail "Workflow quote creation (HTTP $RESP_CODE)"
+fi
+
 section "TEST SUMMARY"
 
 total=$((PASSED + FAILED))